@@ -6,6 +6,8 @@
 //! * Optionally: Add [`TnuaAvian2dSensorShape`] to the sensor entities. This means the entity of
 //!   the characters controlled by Tnua, but also other things like the entity generated by
 //!   `TnuaCrouchEnforcer`, that can be affected with a closure.
+//! * Optionally: Add [`TnuaAvian2dKinematicCharacter`] to the character entity to move it as a
+//!   kinematic body instead of a dynamic one.
 mod spatial_ext;
 
 use avian2d::math::{AdjustPrecision, AsF32};
@@ -14,14 +16,15 @@ use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
 use bevy::prelude::*;
 use bevy_tnua_physics_integration_layer::data_for_backends::{
     TnuaGhostPlatform, TnuaGhostSensor, TnuaGravity, TnuaMotor, TnuaNotPlatform,
-    TnuaProximitySensor, TnuaProximitySensorOutput, TnuaRigidBodyTracker, TnuaToggle,
+    TnuaPredictiveSensorCast, TnuaProximitySensor, TnuaProximitySensorOutput, TnuaRigidBodyTracker,
+    TnuaToggle,
 };
 use bevy_tnua_physics_integration_layer::math::*;
 use bevy_tnua_physics_integration_layer::subservient_sensors::TnuaSubservientSensor;
 pub use spatial_ext::TnuaSpatialExtAvian2d;
 
 use bevy_tnua_physics_integration_layer::*;
-use obstacle_radar::TnuaObstacleRadar;
+use obstacle_radar::{TnuaObstacleRadar, TnuaObstacleRadarShape};
 
 /// Add this plugin to use avian2d as a physics backend.
 ///
@@ -73,7 +76,11 @@ impl Plugin for TnuaAvian2dPlugin {
         );
         app.add_systems(
             self.schedule,
-            apply_motors_system.in_set(TnuaPipelineStages::Motors),
+            (
+                apply_motors_system,
+                apply_motors_to_kinematic_characters_system,
+            )
+                .in_set(TnuaPipelineStages::Motors),
         );
         app.register_required_components::<TnuaSubservientSensor, Position>();
         app.register_required_components::<TnuaSubservientSensor, Rotation>();
@@ -85,6 +92,41 @@ impl Plugin for TnuaAvian2dPlugin {
 #[derive(Component)]
 pub struct TnuaAvian2dSensorShape(pub Collider);
 
+/// Add this component to a character entity to move it as a kinematic body.
+///
+/// Instead of applying the motor as forces on a dynamic rigid body, Tnua will integrate the motor
+/// and the gravity into the character's velocity itself, and resolve collisions by casting the
+/// character's [`Collider`] along its movement and sliding it along whatever it hits. The
+/// resolved movement is written to the [`LinearVelocity`], and avian moves the kinematic body
+/// accordingly.
+///
+/// The [`Collider`] can be on the character entity itself or on one of its direct children. If
+/// Tnua cannot find it, it will log a warning and leave the character in place.
+///
+/// This will make the entity a [`RigidBody::Kinematic`] unless it is spawned with a different
+/// [`RigidBody`] component.
+///
+/// Note that kinematic characters are not pushed by other bodies, and only push dynamic bodies
+/// through the physics engine's contact resolution with kinematic bodies.
+#[derive(Component, Clone, Debug)]
+#[require(RigidBody(|| RigidBody::Kinematic))]
+pub struct TnuaAvian2dKinematicCharacter {
+    /// The gap to keep between the character's collider and the surfaces it slides on.
+    pub collision_margin: Float,
+
+    /// The maximum number of times the movement can be deflected by collisions in a single frame.
+    pub max_slide_iterations: usize,
+}
+
+impl Default for TnuaAvian2dKinematicCharacter {
+    fn default() -> Self {
+        Self {
+            collision_margin: 0.01,
+            max_slide_iterations: 4,
+        }
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn update_rigid_body_trackers_system(
     gravity: Res<Gravity>,
@@ -125,17 +167,21 @@ fn update_rigid_body_trackers_system(
 
 #[allow(clippy::type_complexity)]
 fn update_proximity_sensors_system(
+    time: Res<Time>,
     spatial_query_pipeline: Res<SpatialQueryPipeline>,
     mut query: Query<(
         Entity,
         &Position,
         &Rotation,
+        &GlobalTransform,
         Option<&Collider>,
         &mut TnuaProximitySensor,
         Option<&TnuaAvian2dSensorShape>,
         Option<&mut TnuaGhostSensor>,
         Option<&TnuaSubservientSensor>,
         Option<&TnuaToggle>,
+        Option<&LinearVelocity>,
+        Has<TnuaPredictiveSensorCast>,
     )>,
     collision_layers_query: Query<&CollisionLayers>,
     other_object_query: Query<(
@@ -147,29 +193,46 @@ fn update_proximity_sensors_system(
         Has<TnuaNotPlatform>,
     )>,
 ) {
+    let frame_duration = time.delta_secs_f64() as Float;
     query.par_iter_mut().for_each(
         |(
             owner_entity,
             position,
             rotation,
+            global_transform,
             collider,
             mut sensor,
             shape,
             mut ghost_sensor,
             subservient,
             tnua_toggle,
+            linear_velocity,
+            predictive_cast,
         )| {
             match tnua_toggle.copied().unwrap_or_default() {
                 TnuaToggle::Disabled => return,
                 TnuaToggle::SenseOnly => {}
                 TnuaToggle::Enabled => {}
             }
-            let transform = Transform {
-                translation: position.f32().extend(0.0),
-                rotation: Quaternion::from(*rotation).f32(),
-                scale: collider
-                    .map(|collider| collider.scale().f32().extend(1.0))
-                    .unwrap_or(Vec3::ONE),
+            let cast_range = match (predictive_cast, linear_velocity) {
+                (true, Some(linear_velocity)) => {
+                    sensor.predictive_cast_range(linear_velocity.0.extend(0.0), frame_duration)
+                }
+                _ => sensor.cast_range,
+            };
+            let transform = if subservient.is_some() {
+                // Subservient sensors usually don't have a rigid body or a collider, so avian only
+                // updates their `Position` and `Rotation` when their `GlobalTransform` changes -
+                // which does not happen if it was already propagated when they were added.
+                global_transform.compute_transform()
+            } else {
+                Transform {
+                    translation: position.f32().extend(0.0),
+                    rotation: Quaternion::from(*rotation).f32(),
+                    scale: collider
+                        .map(|collider| collider.scale().f32().extend(1.0))
+                        .unwrap_or(Vec3::ONE),
+                }
             };
             let cast_origin = transform.transform_point(sensor.cast_origin.f32());
             let cast_direction = sensor.cast_direction;
@@ -299,6 +362,9 @@ fn update_proximity_sensors_system(
                 }
             };
 
+            // The hits are not reported in order, so they must be sorted before picking the first
+            // tangible one.
+            let mut cast_results = Vec::new();
             let query_filter = SpatialQueryFilter::from_excluded_entities([owner_entity]);
             if let Some(TnuaAvian2dSensorShape(shape)) = shape {
                 spatial_query_pipeline.shape_hits_callback(
@@ -307,31 +373,32 @@ fn update_proximity_sensors_system(
                     0.0,
                     cast_direction_2d,
                     &ShapeCastConfig {
-                        max_distance: sensor.cast_range,
+                        max_distance: cast_range,
                         ignore_origin_penetration: true,
                         ..default()
                     },
                     &query_filter,
                     #[allow(clippy::useless_conversion)]
                     |shape_hit_data| {
-                        apply_cast(CastResult {
+                        cast_results.push(CastResult {
                             entity: shape_hit_data.entity,
                             proximity: shape_hit_data.distance,
                             intersection_point: shape_hit_data.point1,
                             normal: Dir3::new(shape_hit_data.normal1.extend(0.0).f32())
                                 .unwrap_or_else(|_| -cast_direction),
-                        })
+                        });
+                        true
                     },
                 );
             } else {
                 spatial_query_pipeline.ray_hits_callback(
                     cast_origin.truncate().adjust_precision(),
                     cast_direction_2d,
-                    sensor.cast_range,
+                    cast_range,
                     true,
                     &query_filter,
                     |ray_hit_data| {
-                        apply_cast(CastResult {
+                        cast_results.push(CastResult {
                             entity: ray_hit_data.entity,
                             proximity: ray_hit_data.distance,
                             intersection_point: cast_origin.truncate().adjust_precision()
@@ -339,39 +406,110 @@ fn update_proximity_sensors_system(
                                     * cast_direction_2d.adjust_precision(),
                             normal: Dir3::new(ray_hit_data.normal.extend(0.0).f32())
                                 .unwrap_or_else(|_| -cast_direction),
-                        })
+                        });
+                        true
                     },
                 );
             }
+            cast_results.sort_by(|a, b| a.proximity.total_cmp(&b.proximity));
+            for cast_result in cast_results {
+                if !apply_cast(cast_result) {
+                    break;
+                }
+            }
             sensor.output = final_sensor_output;
         },
     );
 }
 
+#[allow(clippy::type_complexity)]
 fn update_obstacle_radars_system(
     spatial_query_pipeline: Res<SpatialQueryPipeline>,
     gravity: Res<Gravity>,
-    mut radars_query: Query<(Entity, &mut TnuaObstacleRadar, &Position)>,
+    collision_layers_query: Query<&CollisionLayers>,
+    mut radars_query: Query<(
+        Entity,
+        &mut TnuaObstacleRadar,
+        &Position,
+        &Rotation,
+        Option<&CollisionLayers>,
+        Option<&TnuaRigidBodyTracker>,
+    )>,
 ) {
     if radars_query.is_empty() {
         return;
     }
-    for (radar_owner_entity, mut radar, radar_position) in radars_query.iter_mut() {
+    for (
+        radar_owner_entity,
+        mut radar,
+        radar_position,
+        radar_rotation,
+        collision_layers,
+        tracker,
+    ) in radars_query.iter_mut()
+    {
+        let gravity = tracker
+            .map(|tracker| tracker.gravity)
+            .unwrap_or(gravity.0.extend(0.0));
+        let up_direction = Dir3::new(-gravity.f32()).unwrap_or(Dir3::Y);
         radar.pre_marking_update(
             radar_owner_entity,
             radar_position.0.extend(0.0),
-            Dir3::new(gravity.0.f32().extend(0.0)).unwrap_or(Dir3::Y),
+            up_direction,
         );
+        let (radar_collider, shape_rotation) = match radar.shape {
+            TnuaObstacleRadarShape::Cylinder { radius, height } => {
+                let up_direction = up_direction.adjust_precision().truncate();
+                (
+                    Collider::rectangle(2.0 * radius, height),
+                    Vector2::Y.angle_to(up_direction),
+                )
+            }
+            TnuaObstacleRadarShape::Sphere { radius } => (Collider::circle(radius), 0.0),
+            TnuaObstacleRadarShape::Cuboid { half_extents } => (
+                Collider::rectangle(2.0 * half_extents.x, 2.0 * half_extents.y),
+                radar_rotation.as_radians(),
+            ),
+            TnuaObstacleRadarShape::Cone {
+                radius,
+                length,
+                direction,
+            } => {
+                let Some(direction) = direction.adjust_precision().truncate().try_normalize()
+                else {
+                    warn_once!(
+                        "Obstacle radar cone direction {direction:?} has no component in the XY plane"
+                    );
+                    continue;
+                };
+                let direction = *radar_rotation * direction;
+                let base_center = length * direction;
+                let base_offset = radius * direction.perp();
+                (
+                    Collider::triangle(
+                        Vector2::ZERO,
+                        base_center - base_offset,
+                        base_center + base_offset,
+                    ),
+                    0.0,
+                )
+            }
+        };
+        let collision_layers = collision_layers.copied().unwrap_or_default();
+        let query_filter = SpatialQueryFilter::from_excluded_entities([radar_owner_entity]);
         spatial_query_pipeline.shape_intersections_callback(
-            &Collider::rectangle(2.0 * radar.radius, radar.height),
+            &radar_collider,
             radar_position.0,
-            Default::default(),
-            &SpatialQueryFilter::DEFAULT,
+            shape_rotation,
+            &query_filter,
             |obstacle_entity| {
-                if radar_owner_entity == obstacle_entity {
-                    return true;
+                let obstacle_collision_layers = collision_layers_query
+                    .get(obstacle_entity)
+                    .copied()
+                    .unwrap_or_default();
+                if collision_layers.interacts_with(obstacle_collision_layers) {
+                    radar.mark_seen(obstacle_entity);
                 }
-                radar.mark_seen(obstacle_entity);
                 true
             },
         );
@@ -380,17 +518,20 @@ fn update_obstacle_radars_system(
 
 #[allow(clippy::type_complexity)]
 fn apply_motors_system(
-    mut query: Query<(
-        &TnuaMotor,
-        &mut LinearVelocity,
-        &mut AngularVelocity,
-        &ComputedMass,
-        &ComputedAngularInertia,
-        &mut ExternalForce,
-        &mut ExternalTorque,
-        Option<&TnuaToggle>,
-        Option<&TnuaGravity>,
-    )>,
+    mut query: Query<
+        (
+            &TnuaMotor,
+            &mut LinearVelocity,
+            &mut AngularVelocity,
+            &ComputedMass,
+            &ComputedAngularInertia,
+            &mut ExternalForce,
+            &mut ExternalTorque,
+            Option<&TnuaToggle>,
+            Option<&TnuaGravity>,
+        ),
+        Without<TnuaAvian2dKinematicCharacter>,
+    >,
 ) {
     for (
         motor,
@@ -432,3 +573,124 @@ fn apply_motors_system(
         }
     }
 }
+
+#[allow(clippy::type_complexity)]
+fn apply_motors_to_kinematic_characters_system(
+    time: Res<Time>,
+    gravity: Res<Gravity>,
+    spatial_query_pipeline: Res<SpatialQueryPipeline>,
+    mut query: Query<(
+        Entity,
+        &TnuaAvian2dKinematicCharacter,
+        &TnuaMotor,
+        &Position,
+        &Rotation,
+        Option<&Children>,
+        &mut LinearVelocity,
+        &mut AngularVelocity,
+        Option<&TnuaToggle>,
+        Option<&TnuaGravity>,
+    )>,
+    colliders_query: Query<(&Collider, &ColliderTransform, Option<&CollisionLayers>)>,
+    sensors_query: Query<(), With<Sensor>>,
+) {
+    let frame_duration = time.delta_secs_f64() as Float;
+    if frame_duration == 0.0 {
+        return;
+    }
+    for (
+        owner_entity,
+        kinematic_character,
+        motor,
+        position,
+        rotation,
+        children,
+        mut linear_velocity,
+        mut angular_velocity,
+        tnua_toggle,
+        tnua_gravity,
+    ) in query.iter_mut()
+    {
+        match tnua_toggle.copied().unwrap_or_default() {
+            TnuaToggle::Disabled | TnuaToggle::SenseOnly => continue,
+            TnuaToggle::Enabled => {}
+        }
+
+        // The collider may be on the character entity itself or on one of its children.
+        let Some((collider_entity, (collider, collider_transform, collision_layers))) =
+            std::iter::once(owner_entity)
+                .chain(children.into_iter().flatten().copied())
+                .find_map(|entity| Some((entity, colliders_query.get(entity).ok()?)))
+        else {
+            warn_once!(
+                "{owner_entity} has a TnuaAvian2dKinematicCharacter but no collider on it or on \
+                 its children, so Tnua cannot move it"
+            );
+            continue;
+        };
+
+        // Kinematic bodies are not affected by forces or by the physics engine's gravity, so the
+        // motor and the gravity are integrated into the velocity here.
+        let mut velocity = linear_velocity.0;
+        if motor.lin.boost.is_finite() {
+            velocity += motor.lin.boost.truncate();
+        }
+        if motor.lin.acceleration.is_finite() {
+            velocity += motor.lin.acceleration.truncate() * frame_duration;
+        }
+        velocity += tnua_gravity.map(|g| g.0.truncate()).unwrap_or(gravity.0) * frame_duration;
+
+        let mut query_filter =
+            SpatialQueryFilter::from_excluded_entities([owner_entity, collider_entity]);
+        if let Some(collision_layers) = collision_layers {
+            query_filter = query_filter.with_mask(collision_layers.filters);
+        }
+        let predicate = |entity: Entity| !sensors_query.contains(entity);
+
+        let collider_origin = position.0 + *rotation * collider_transform.translation;
+        let collider_rotation = *rotation * collider_transform.rotation;
+        let mut cast_origin = collider_origin;
+        let mut remaining_displacement = velocity * frame_duration;
+        for _ in 0..kinematic_character.max_slide_iterations {
+            let Ok((direction, distance)) = Dir2::new_and_length(remaining_displacement.f32())
+            else {
+                break;
+            };
+            let distance = distance.adjust_precision();
+            let Some(hit) = spatial_query_pipeline.cast_shape_predicate(
+                collider,
+                cast_origin,
+                collider_rotation.as_radians(),
+                direction,
+                &ShapeCastConfig {
+                    max_distance: distance + kinematic_character.collision_margin,
+                    ignore_origin_penetration: true,
+                    ..default()
+                },
+                &query_filter,
+                &predicate,
+            ) else {
+                cast_origin += remaining_displacement;
+                remaining_displacement = Vector2::ZERO;
+                break;
+            };
+            let travel = (hit.distance - kinematic_character.collision_margin).clamp(0.0, distance);
+            let direction = direction.adjust_precision();
+            cast_origin += travel * direction;
+            remaining_displacement -= travel * direction;
+            let Some(normal) = hit.normal1.try_normalize() else {
+                break;
+            };
+            remaining_displacement -= remaining_displacement.dot(normal).min(0.0) * normal;
+        }
+
+        // Let the physics engine move the kinematic body to the resolved position.
+        linear_velocity.0 = (cast_origin - collider_origin) / frame_duration;
+        if motor.ang.boost.is_finite() {
+            angular_velocity.0 += motor.ang.boost.z;
+        }
+        if motor.ang.acceleration.is_finite() {
+            angular_velocity.0 += motor.ang.acceleration.z * frame_duration;
+        }
+    }
+}
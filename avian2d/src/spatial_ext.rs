@@ -2,13 +2,23 @@ use avian2d::prelude::*;
 use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_tnua_physics_integration_layer::{
     math::{Float, Vector3},
-    spatial_ext::{TnuaPointProjectionResult, TnuaSpatialExt},
+    spatial_ext::{TnuaDynamicBodyData, TnuaPointProjectionResult, TnuaSpatialExt},
 };
 
 #[derive(SystemParam)]
 pub struct TnuaSpatialExtAvian2d<'w, 's> {
     colliders_query: Query<'w, 's, (&'static Collider, &'static Position, &'static Rotation)>,
     collision_configuration_query: Query<'w, 's, (Option<&'static CollisionLayers>, Has<Sensor>)>,
+    collider_parents_query: Query<'w, 's, &'static ColliderParent>,
+    rigid_bodies_query: Query<
+        'w,
+        's,
+        (
+            &'static RigidBody,
+            &'static ComputedMass,
+            &'static LinearVelocity,
+        ),
+    >,
 }
 
 impl TnuaSpatialExt for TnuaSpatialExtAvian2d<'_, '_> {
@@ -73,4 +83,20 @@ impl TnuaSpatialExt for TnuaSpatialExtAvian2d<'_, '_> {
             .unwrap_or_default()
             .interacts_with(layers2.copied().unwrap_or_default())
     }
+
+    fn dynamic_body_data(&self, entity: Entity) -> Option<TnuaDynamicBodyData> {
+        let body_entity = self
+            .collider_parents_query
+            .get(entity)
+            .map_or(entity, |parent| parent.get());
+        let (rigid_body, mass, linear_velocity) = self.rigid_bodies_query.get(body_entity).ok()?;
+        if !rigid_body.is_dynamic() {
+            return None;
+        }
+        Some(TnuaDynamicBodyData {
+            body_entity,
+            mass: mass.value(),
+            linvel: linear_velocity.0.extend(0.0),
+        })
+    }
 }
@@ -0,0 +1,44 @@
+use avian2d::prelude::*;
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+use bevy_tnua_avian2d::{TnuaAvian2dPlugin, TnuaSpatialExtAvian2d};
+use bevy_tnua_physics_integration_layer::conformance::{run_all_checks, TnuaConformanceBackend};
+use bevy_tnua_physics_integration_layer::math::{Float, Vector3};
+
+struct Avian2dBackend;
+
+impl TnuaConformanceBackend for Avian2dBackend {
+    type SpatialExt = TnuaSpatialExtAvian2d<'static, 'static>;
+
+    fn setup_app(app: &mut App) -> InternedScheduleLabel {
+        app.add_plugins((
+            PhysicsPlugins::new(FixedPostUpdate),
+            TnuaAvian2dPlugin::new(FixedUpdate),
+        ));
+        FixedUpdate.intern()
+    }
+
+    fn insert_character_body(entity: &mut EntityWorldMut, radius: Float) {
+        entity.insert((
+            RigidBody::Dynamic,
+            Collider::circle(radius),
+            LockedAxes::ROTATION_LOCKED,
+            Friction::ZERO.with_combine_rule(CoefficientCombine::Min),
+        ));
+    }
+
+    fn insert_static_cuboid(entity: &mut EntityWorldMut, half_extents: Vector3) {
+        let size = 2.0 * half_extents;
+        entity.insert((RigidBody::Static, Collider::rectangle(size.x, size.y)));
+    }
+
+    fn insert_passable_cuboid(entity: &mut EntityWorldMut, half_extents: Vector3) {
+        Self::insert_static_cuboid(entity, half_extents);
+        entity.insert(CollisionLayers::new(LayerMask(0b10), LayerMask(0b10)));
+    }
+}
+
+#[test]
+fn conformance() {
+    run_all_checks::<Avian2dBackend>();
+}
@@ -1,14 +1,24 @@
 use avian3d::prelude::*;
 use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_tnua_physics_integration_layer::{
-    math::{Float, Vector3},
-    spatial_ext::{TnuaPointProjectionResult, TnuaSpatialExt},
+    math::{AdjustPrecision, Float, Vector3},
+    spatial_ext::{TnuaDynamicBodyData, TnuaPointProjectionResult, TnuaSpatialExt},
 };
 
 #[derive(SystemParam)]
 pub struct TnuaSpatialExtAvian3d<'w, 's> {
     colliders_query: Query<'w, 's, (&'static Collider, &'static Position, &'static Rotation)>,
     collision_configuration_query: Query<'w, 's, (Option<&'static CollisionLayers>, Has<Sensor>)>,
+    collider_parents_query: Query<'w, 's, &'static ColliderParent>,
+    rigid_bodies_query: Query<
+        'w,
+        's,
+        (
+            &'static RigidBody,
+            &'static ComputedMass,
+            &'static LinearVelocity,
+        ),
+    >,
 }
 
 impl TnuaSpatialExt for TnuaSpatialExtAvian3d<'_, '_> {
@@ -70,4 +80,20 @@ impl TnuaSpatialExt for TnuaSpatialExtAvian3d<'_, '_> {
             .unwrap_or_default()
             .interacts_with(layers2.copied().unwrap_or_default())
     }
+
+    fn dynamic_body_data(&self, entity: Entity) -> Option<TnuaDynamicBodyData> {
+        let body_entity = self
+            .collider_parents_query
+            .get(entity)
+            .map_or(entity, |parent| parent.get());
+        let (rigid_body, mass, linear_velocity) = self.rigid_bodies_query.get(body_entity).ok()?;
+        if !rigid_body.is_dynamic() {
+            return None;
+        }
+        Some(TnuaDynamicBodyData {
+            body_entity,
+            mass: mass.value(),
+            linvel: linear_velocity.0.adjust_precision(),
+        })
+    }
 }
@@ -6,13 +6,13 @@ use bevy::prelude::*;
 use bevy_rapier3d::{prelude as rapier, prelude::*};
 use bevy_tnua::builtins::TnuaBuiltinCrouch;
 use bevy_tnua::control_helpers::{
-    TnuaBlipReuseAvoidance, TnuaCrouchEnforcer, TnuaCrouchEnforcerPlugin,
-    TnuaSimpleAirActionsCounter, TnuaSimpleFallThroughPlatformsHelper,
+    TnuaBlipReuseAvoidance, TnuaCeilingSensor, TnuaCeilingSensorPlugin, TnuaCrouchEnforcer,
+    TnuaCrouchEnforcerPlugin, TnuaSimpleAirActionsCounter, TnuaSimpleFallThroughPlatformsHelper,
 };
 #[allow(unused_imports)]
 use bevy_tnua::math::{float_consts, AsF32, Vector3};
 use bevy_tnua::{prelude::*, TnuaObstacleRadar};
-use bevy_tnua::{TnuaAnimatingState, TnuaGhostSensor, TnuaToggle};
+use bevy_tnua::{TnuaAnimatingState, TnuaGhostSensor, TnuaPredictiveSensorCast, TnuaToggle};
 #[cfg(feature = "avian3d")]
 use bevy_tnua_avian3d::*;
 #[cfg(feature = "rapier3d")]
@@ -94,10 +94,15 @@ fn main() {
             // This plugin supports `TnuaCrouchEnforcer`, which prevents the character from standing up
             // while obstructed by an obstacle.
             app.add_plugins(TnuaCrouchEnforcerPlugin::default());
+
+            // This plugin supports `TnuaCeilingSensor`, which stops the jump when the character
+            // hits its head.
+            app.add_plugins(TnuaCeilingSensorPlugin::default());
         }
         ScheduleToUse::FixedUpdate => {
             app.add_plugins(TnuaControllerPlugin::new(FixedUpdate));
             app.add_plugins(TnuaCrouchEnforcerPlugin::new(FixedUpdate));
+            app.add_plugins(TnuaCeilingSensorPlugin::new(FixedUpdate));
         }
     }
 
@@ -208,6 +213,8 @@ fn setup_player(mut commands: Commands, asset_server: Res<AssetServer>) {
         walk: TnuaBuiltinWalk {
             float_height: 2.0,
             max_slope: float_consts::FRAC_PI_4,
+            landing_prediction: true,
+            platform_angvel_inheritance: 1.0,
             ..Default::default()
         },
         actions_in_air: 1,
@@ -376,12 +383,29 @@ fn setup_player(mut commands: Commands, asset_server: Res<AssetServer>) {
         cmd.insert(TnuaAvian3dSensorShape(avian::Collider::cylinder(0.5, 0.0)));
     }));
 
+    // The ceiling sensor makes the character stop rising when it hits its head during a jump. Like
+    // the crouch enforcer, it casts upward from the middle of the character - so the range covers
+    // the distance to the top of the collider plus a small margin.
+    cmd.insert(TnuaCeilingSensor::new(0.5 * Vector3::Y, 0.6, |cmd| {
+        #[cfg(feature = "rapier3d")]
+        cmd.insert(TnuaRapier3dSensorShape(rapier::Collider::cylinder(
+            0.0, 0.45,
+        )));
+        #[cfg(feature = "avian3d")]
+        cmd.insert(TnuaAvian3dSensorShape(avian::Collider::cylinder(0.45, 0.0)));
+    }));
+
     // The ghost sensor is used for detecting ghost platforms - platforms configured in the physics
     // backend to not contact with the character (or detect the contact but not apply physical
     // forces based on it) and marked with the `TnuaGhostPlatform` component. These can then be
     // used as one-way platforms.
     cmd.insert(TnuaGhostSensor::default());
 
+    // This makes the physics backend extend the proximity sensor's range by the distance the
+    // character is expected to fall in the current frame, so that `landing_prediction` can start
+    // braking before a fast fall takes the character into the ground.
+    cmd.insert(TnuaPredictiveSensorCast);
+
     // This helper is used to operate the ghost sensor and ghost platforms and implement
     // fall-through behavior where the player can intentionally fall through a one-way platform.
     cmd.insert(TnuaSimpleFallThroughPlatformsHelper::default());
@@ -8,10 +8,10 @@ use bevy::window::{CursorGrabMode, PrimaryWindow};
 use bevy_rapier3d::{prelude as rapier, prelude::*};
 use bevy_tnua::builtins::TnuaBuiltinCrouch;
 use bevy_tnua::control_helpers::{
-    TnuaBlipReuseAvoidance, TnuaCrouchEnforcer, TnuaCrouchEnforcerPlugin,
+    TnuaBlipReuseAvoidance, TnuaCameraRelativeInput, TnuaCrouchEnforcer, TnuaCrouchEnforcerPlugin,
     TnuaSimpleAirActionsCounter, TnuaSimpleFallThroughPlatformsHelper,
 };
-use bevy_tnua::math::{float_consts, AdjustPrecision, AsF32, Float, Quaternion, Vector3};
+use bevy_tnua::math::{float_consts, Float, Vector3};
 use bevy_tnua::{prelude::*, TnuaObstacleRadar};
 use bevy_tnua::{TnuaAnimatingState, TnuaGhostSensor, TnuaToggle};
 #[cfg(feature = "avian3d")]
@@ -25,7 +25,6 @@ use tnua_demos_crate::character_animating_systems::platformer_animating_systems:
 };
 use tnua_demos_crate::character_control_systems::platformer_control_systems::{
     apply_platformer_controls, CharacterMotionConfigForPlatformerDemo, FallingThroughControlScheme,
-    ForwardFromCamera,
 };
 use tnua_demos_crate::character_control_systems::Dimensionality;
 use tnua_demos_crate::character_control_systems::{
@@ -225,7 +224,9 @@ fn setup_player(mut commands: Commands, asset_server: Res<AssetServer>) {
         climb_speed: 10.0,
     });
 
-    cmd.insert(ForwardFromCamera::default());
+    // This helper converts the keyboard input to a movement relative to the camera. The speed is
+    // applied by the control system, so the helper only needs to provide the direction.
+    cmd.insert(TnuaCameraRelativeInput::default());
 
     // An entity's Tnua behavior can be toggled individually with this component, if inserted.
     cmd.insert(TnuaToggle::default());
@@ -424,7 +425,7 @@ fn grab_ungrab_mouse(
 fn apply_camera_controls(
     primary_window_query: Query<&Window, With<PrimaryWindow>>,
     mut mouse_motion: EventReader<MouseMotion>,
-    mut player_character_query: Query<(&GlobalTransform, &mut ForwardFromCamera)>,
+    player_character_query: Query<&GlobalTransform, With<TnuaCameraRelativeInput>>,
     mut camera_query: Query<&mut Transform, With<Camera>>,
 ) {
     let mouse_controls_camera = primary_window_query
@@ -436,27 +437,21 @@ fn apply_camera_controls(
         mouse_motion.clear();
         Vec2::ZERO
     };
-    let Ok((player_transform, mut forward_from_camera)) = player_character_query.get_single_mut()
-    else {
+    let Ok(player_transform) = player_character_query.get_single() else {
         return;
     };
 
-    let yaw = Quaternion::from_rotation_y(-0.01 * total_delta.x.adjust_precision());
-    forward_from_camera.forward = yaw.mul_vec3(forward_from_camera.forward);
-
-    let pitch = 0.005 * total_delta.y.adjust_precision();
-    forward_from_camera.pitch_angle = (forward_from_camera.pitch_angle + pitch)
-        .clamp(-float_consts::FRAC_PI_2, float_consts::FRAC_PI_2);
-
     for mut camera in camera_query.iter_mut() {
-        camera.translation = player_transform.translation()
-            + -5.0 * forward_from_camera.forward.f32()
-            + 1.0 * Vec3::Y;
-        camera.look_to(forward_from_camera.forward.f32(), Vec3::Y);
-        let pitch_axis = camera.left();
-        camera.rotate_around(
-            player_transform.translation(),
-            Quat::from_axis_angle(*pitch_axis, forward_from_camera.pitch_angle.f32()),
-        );
+        // The camera's own rotation holds the yaw and the pitch. `TnuaCameraRelativeInput` uses
+        // it (via the camera's `GlobalTransform`) to determine where the character should move.
+        let (yaw, pitch, _) = camera.rotation.to_euler(EulerRot::YXZ);
+        let yaw = yaw - 0.01 * total_delta.x;
+        // Keep the pitch slightly away from straight up or down, so that the yaw can still be
+        // extracted from the rotation.
+        let max_pitch = std::f32::consts::FRAC_PI_2 - 0.01;
+        let pitch = (pitch - 0.005 * total_delta.y).clamp(-max_pitch, max_pitch);
+        camera.rotation = Quat::from_euler(EulerRot::YXZ, yaw, pitch, 0.0);
+        camera.translation =
+            player_transform.translation() + camera.rotation * Vec3::new(0.0, 1.0, 5.0);
     }
 }
@@ -69,8 +69,10 @@ pub fn animate_platformer_character(
                             AnimationState::Jumping
                         }
                         TnuaBuiltinJumpState::MaintainingJump { .. } => AnimationState::Jumping,
-                        TnuaBuiltinJumpState::StoppedMaintainingJump => AnimationState::Jumping,
-                        TnuaBuiltinJumpState::FallSection => AnimationState::Falling,
+                        TnuaBuiltinJumpState::StoppedMaintainingJump { .. } => {
+                            AnimationState::Jumping
+                        }
+                        TnuaBuiltinJumpState::FallSection { .. } => AnimationState::Falling,
                     }
                 }
                 Some(TnuaBuiltinCrouch::NAME) => {
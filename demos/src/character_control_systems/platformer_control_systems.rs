@@ -7,9 +7,10 @@ use bevy::{
 #[cfg(feature = "egui")]
 use bevy_egui::{egui, EguiContexts};
 use bevy_tnua::control_helpers::{
-    TnuaCrouchEnforcer, TnuaSimpleAirActionsCounter, TnuaSimpleFallThroughPlatformsHelper,
+    TnuaCameraRelativeInput, TnuaCrouchEnforcer, TnuaSimpleAirActionsCounter,
+    TnuaSimpleFallThroughPlatformsHelper,
 };
-use bevy_tnua::math::{AdjustPrecision, AsF32, Float, Vector3};
+use bevy_tnua::math::{AdjustPrecision, AsF32, Float, Vector2, Vector3};
 use bevy_tnua::radar_lens::{TnuaBlipSpatialRelation, TnuaRadarLens};
 use bevy_tnua::{
     builtins::{
@@ -60,9 +61,9 @@ pub fn apply_platformer_controls(
         // air dash per jump - only a single "pool" of air action "energy" shared by all air
         // actions.
         &mut TnuaSimpleAirActionsCounter,
-        // This is used in the shooter-like demo to control the forward direction of the
-        // character.
-        Option<&ForwardFromCamera>,
+        // This is used in the shooter-like demo to make the movement relative to the camera, and
+        // to control the forward direction of the character.
+        Option<&TnuaCameraRelativeInput>,
         // This is used to detect all the colliders in a small area around the character.
         &TnuaObstacleRadar,
         // This is used to avoid re-initiating actions on the same obstacles until we return to
@@ -77,6 +78,8 @@ pub fn apply_platformer_controls(
     // This is used to determine the qualities of the obstacles (e.g. whether or not they are
     // climbable)
     obstacle_query: Query<ObstacleQueryHelper>,
+    // This is used in the shooter-like demo, together with `TnuaCameraRelativeInput`.
+    camera_query: Query<&GlobalTransform, With<Camera3d>>,
 ) {
     #[cfg(feature = "egui")]
     if egui_context.ctx_mut().wants_keyboard_input() {
@@ -97,7 +100,7 @@ pub fn apply_platformer_controls(
         ghost_sensor,
         mut fall_through_helper,
         mut air_actions_counter,
-        forward_from_camera,
+        camera_relative_input,
         obstacle_radar,
         mut blip_reuse_avoidance,
     ) in query.iter_mut()
@@ -125,11 +128,20 @@ pub fn apply_platformer_controls(
 
         let screen_space_direction = direction.clamp_length_max(1.0);
 
-        let direction = if let Some(forward_from_camera) = forward_from_camera {
-            Transform::default()
-                .looking_to(forward_from_camera.forward.f32(), Vec3::Y)
-                .transform_point(screen_space_direction.f32())
-                .adjust_precision()
+        // In the shooter-like demo the input is relative to the camera. The helper projects the
+        // camera's direction on the ground, so that looking up or down does not slow the
+        // character down.
+        let camera_relative_movement = camera_relative_input.and_then(|camera_relative_input| {
+            let camera_transform = camera_query.get_single().ok()?;
+            Some(camera_relative_input.movement_from_camera_rotation(
+                camera_transform.rotation().adjust_precision(),
+                Vector2::new(screen_space_direction.x, -screen_space_direction.z),
+                Dir3::Y,
+            ))
+        });
+
+        let direction = if let Some(camera_relative_movement) = &camera_relative_movement {
+            camera_relative_movement.direction
         } else {
             screen_space_direction
         };
@@ -143,7 +155,7 @@ pub fn apply_platformer_controls(
         };
         let dash = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
 
-        let turn_in_place = forward_from_camera.is_none()
+        let turn_in_place = camera_relative_input.is_none()
             && keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
 
         let crouch_buttons = match (config.dimensionality, is_climbing) {
@@ -316,6 +328,8 @@ pub fn apply_platformer_controls(
                 1.0
             };
 
+        let radar_lens = TnuaRadarLens::new(obstacle_radar, &spatial_ext);
+
         // The basis is Tnua's most fundamental control command, governing over the character's
         // regular movement. The basis (and, to some extent, the actions as well) contains both
         // configuration - which in this case we copy over from `config.walk` - and controls like
@@ -327,19 +341,20 @@ pub fn apply_platformer_controls(
             } else {
                 direction * speed_factor * config.speed
             },
-            desired_forward: if let Some(forward_from_camera) = forward_from_camera {
+            desired_forward: if let Some(camera_relative_movement) = &camera_relative_movement {
                 // With shooters, we want the character model to follow the camera.
-                Dir3::new(forward_from_camera.forward.f32()).ok()
+                camera_relative_movement.camera_forward
             } else {
                 // For platformers, we only want ot change direction when the character tries to
                 // moves (or when the player explicitly wants to set the direction)
                 Dir3::new(direction.f32()).ok()
             },
+            // The character's collider has a radius of 0.5, so anything closer than that (with some
+            // margin) is touching it.
+            dynamic_contacts: radar_lens.dynamic_contacts(0.6).collect(),
             ..config.walk.clone()
         });
 
-        let radar_lens = TnuaRadarLens::new(obstacle_radar, &spatial_ext);
-
         let already_sliding_on = controller
             .concrete_action::<TnuaBuiltinWallSlide>()
             .and_then(|(action, _)| {
@@ -573,7 +588,7 @@ pub fn apply_platformer_controls(
                 // When set, the `desired_forward` of the dash action "overrides" the
                 // `desired_forward` of the walk basis. Like the displacement, it gets "frozen" -
                 // allowing to easily maintain a forward direction during the dash.
-                desired_forward: if forward_from_camera.is_none() {
+                desired_forward: if camera_relative_input.is_none() {
                     Dir3::new(direction.f32()).ok()
                 } else {
                     // For shooters, we want to allow rotating mid-dash if the player moves the
@@ -676,21 +691,6 @@ impl UiTunable for FallingThroughControlScheme {
     }
 }
 
-#[derive(Component)]
-pub struct ForwardFromCamera {
-    pub forward: Vector3,
-    pub pitch_angle: Float,
-}
-
-impl Default for ForwardFromCamera {
-    fn default() -> Self {
-        Self {
-            forward: Vector3::NEG_Z,
-            pitch_angle: 0.0,
-        }
-    }
-}
-
 /// Since the fixed timestep schedule does not cache just pressed states that happened
 /// in a frame with no fixed updates, we need to cache them ourselves in order to not miss them.
 /// Note that if you use a smarter input manager like LWIM, this is handled for you.
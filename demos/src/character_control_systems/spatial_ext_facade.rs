@@ -3,7 +3,7 @@ use std::marker::PhantomData;
 use bevy::{ecs::system::SystemParam, prelude::*};
 
 use bevy_tnua::math::Vector3;
-use bevy_tnua::spatial_ext::{TnuaPointProjectionResult, TnuaSpatialExt};
+use bevy_tnua::spatial_ext::{TnuaDynamicBodyData, TnuaPointProjectionResult, TnuaSpatialExt};
 #[cfg(feature = "avian2d")]
 use bevy_tnua_avian2d::TnuaSpatialExtAvian2d;
 #[cfg(feature = "avian3d")]
@@ -124,6 +124,19 @@ impl<'w, 's> TnuaSpatialExt for SpatialExtFacade<'w, 's> {
 
         panic!("Running without any physics backend configured");
     }
+
+    fn dynamic_body_data(&self, entity: Entity) -> Option<TnuaDynamicBodyData> {
+        #[cfg(feature = "avian2d")]
+        return self.for_avian2d.dynamic_body_data(entity);
+        #[cfg(feature = "avian3d")]
+        return self.for_avian3d.dynamic_body_data(entity);
+        #[cfg(feature = "rapier2d")]
+        return self.for_rapier2d.dynamic_body_data(entity);
+        #[cfg(feature = "rapier3d")]
+        return self.for_rapier3d.dynamic_body_data(entity);
+
+        panic!("Running without any physics backend configured");
+    }
 }
 
 pub struct ColliderDataFacade<'a, 'w, 's>
@@ -122,6 +122,7 @@ impl UiTunable for TnuaBuiltinWalk {
             egui::Slider::new(&mut self.free_fall_extra_gravity, 0.0..=100.0)
                 .text("Free Fall Extra Gravity"),
         );
+        ui.checkbox(&mut self.landing_prediction, "Landing Prediction");
 
         slider_or_infinity(
             ui,
@@ -142,11 +143,47 @@ impl UiTunable for TnuaBuiltinWalk {
             &mut self.turning_angvel,
             0.0..=70.0,
         );
+        ui.add(
+            egui::Slider::new(&mut self.platform_angvel_inheritance, 0.0..=1.0)
+                .text("Platform Angular Velocity Inheritance"),
+        );
+        ui.add(
+            egui::Slider::new(&mut self.platform_velocity_inheritance, 0.0..=1.0)
+                .text("Platform Velocity Inheritance"),
+        );
+        ui.add(
+            egui::Slider::new(&mut self.launch_platform_velocity_inheritance, 0.0..=1.0)
+                .text("Launch Platform Velocity Inheritance"),
+        );
 
         ui.add(
             egui::Slider::new(&mut self.max_slope, 0.0..=float_consts::FRAC_PI_2)
                 .text("Max Slope (in radians)"),
         );
+        ui.add(
+            egui::Slider::new(&mut self.push_strength, 1.0..=1000.0)
+                .logarithmic(true)
+                .text("Push Strength"),
+        );
+        ui.add(egui::Slider::new(&mut self.shove_yield, 0.0..=1.0).text("Shove Yield"));
+
+        for (name, factors) in [
+            ("Speed", &mut self.directional_speed_factors),
+            ("Acceleration", &mut self.directional_acceleration_factors),
+        ] {
+            ui.add(
+                egui::Slider::new(&mut factors.forward, 0.0..=2.0)
+                    .text(format!("Forward {name} Factor")),
+            );
+            ui.add(
+                egui::Slider::new(&mut factors.backward, 0.0..=2.0)
+                    .text(format!("Backward {name} Factor")),
+            );
+            ui.add(
+                egui::Slider::new(&mut factors.sideways, 0.0..=2.0)
+                    .text(format!("Sideways {name} Factor")),
+            );
+        }
     }
 }
 
@@ -196,6 +233,25 @@ impl UiTunable for TnuaBuiltinJump {
             egui::Slider::new(&mut self.peak_prevention_extra_gravity, 0.0..=100.0)
                 .text("Jump Peak Prevention Extra Gravity"),
         );
+
+        ui.add(
+            egui::Slider::new(&mut self.apex_hang_below_velocity, 0.0..=20.0)
+                .text("Jump Apex Hang Below Velocity"),
+        );
+
+        ui.add(
+            egui::Slider::new(&mut self.apex_hang_reduced_gravity, 0.0..=9.0)
+                .text("Jump Apex Hang Reduced Gravity"),
+        );
+
+        ui.add(egui::Slider::new(&mut self.min_height, 0.0..=10.0).text("Jump Min Height"));
+
+        slider_or_infinity(
+            ui,
+            "Jump Max Fall Speed",
+            &mut self.max_fall_speed,
+            0.0..=100.0,
+        );
     }
 }
 impl UiTunable for TnuaBuiltinCrouch {
@@ -260,6 +316,11 @@ impl UiTunable for TnuaBuiltinKnockback {
             &mut self.air_acceleration_limit,
             0.0..=20.0,
         );
+        ui.add(egui::Slider::new(&mut self.hit_stun_duration, 0.0..=2.0).text("Hit-Stun Duration"));
+        ui.add(egui::Slider::new(&mut self.launch_multiplier, 0.0..=5.0).text("Launch Multiplier"));
+        ui.checkbox(&mut self.tumble, "Tumble");
+        ui.add(egui::Slider::new(&mut self.tumble_spin, 0.0..=20.0).text("Tumble Spin"));
+        slider_or_infinity(ui, "Recovery Window", &mut self.recovery_window, 0.0..=2.0);
     }
 }
 
@@ -241,8 +241,8 @@ fn handle_animating(
                 TnuaBuiltinJumpState::StartingJump { .. } => AnimationState::Jumping,
                 TnuaBuiltinJumpState::SlowDownTooFastSlopeJump { .. } => AnimationState::Jumping,
                 TnuaBuiltinJumpState::MaintainingJump { .. } => AnimationState::Jumping,
-                TnuaBuiltinJumpState::StoppedMaintainingJump => AnimationState::Jumping,
-                TnuaBuiltinJumpState::FallSection => AnimationState::Falling,
+                TnuaBuiltinJumpState::StoppedMaintainingJump { .. } => AnimationState::Jumping,
+                TnuaBuiltinJumpState::FallSection { .. } => AnimationState::Falling,
             }
         }
         // Tnua should only have the `action_name` of the actions you feed to it. If it has
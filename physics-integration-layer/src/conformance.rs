@@ -0,0 +1,653 @@
+//! A conformance suite for physics integration crates.
+//!
+//! The [crate documentation](crate) lists the obligations of a physics integration crate. This
+//! module builds small standard scenes and asserts that a backend fulfills these obligations, so
+//! that every backend - the ones maintained in Tnua's repository and third-party ones - can check
+//! that it behaves the same.
+//!
+//! To use it, implement [`TnuaConformanceBackend`] for a marker type and call
+//! [`run_all_checks`] (or the individual `check_*` functions) from a test:
+//!
+//! ```ignore
+//! struct MyBackend;
+//!
+//! impl TnuaConformanceBackend for MyBackend {
+//!     type SpatialExt = TnuaSpatialExtMyPhysics<'static, 'static>;
+//!
+//!     fn setup_app(app: &mut App) -> InternedScheduleLabel {
+//!         app.add_plugins((MyPhysicsPlugins::default(), TnuaMyPhysicsPlugin::default()));
+//!         Update.intern()
+//!     }
+//!
+//!     fn insert_character_body(entity: &mut EntityWorldMut, radius: Float) {
+//!         entity.insert((MyRigidBody::Dynamic, MyCollider::ball(radius)));
+//!     }
+//!
+//!     // ...
+//! }
+//!
+//! #[test]
+//! fn conformance() {
+//!     bevy_tnua_physics_integration_layer::conformance::run_all_checks::<MyBackend>();
+//! }
+//! ```
+//!
+//! All the scenes are built in the XY plane, with gravity pointing in the negative Y direction,
+//! so that they can be used with 2D backends as well.
+//!
+//! Requires the `conformance` feature.
+use std::time::Duration;
+
+use bevy::ecs::schedule::InternedScheduleLabel;
+use bevy::ecs::system::{SystemParam, SystemState};
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+
+use crate::data_for_backends::{
+    TnuaGhostPlatform, TnuaGhostSensor, TnuaGravity, TnuaMotor, TnuaNotPlatform,
+    TnuaPredictiveSensorCast, TnuaProximitySensor, TnuaRigidBodyTracker, TnuaToggle, TnuaVelChange,
+};
+use crate::math::{AdjustPrecision, AsF32, Float, Vector3};
+use crate::obstacle_radar::{TnuaObstacleRadar, TnuaObstacleRadarShape};
+use crate::spatial_ext::TnuaSpatialExt;
+use crate::subservient_sensors::TnuaSubservientSensor;
+use crate::{TnuaPipelineStages, TnuaSystemSet};
+
+/// The duration of a single frame in the conformance scenes.
+pub const FRAME_DURATION: f64 = 1.0 / 60.0;
+
+/// The tolerance used when comparing distances and velocities.
+const TOLERANCE: Float = 0.05;
+
+/// The number of frames to run before checking the results of a change in the scene.
+const SETTLE_FRAMES: usize = 5;
+
+/// The radius of the character's ball collider.
+const CHARACTER_RADIUS: Float = 0.5;
+
+/// Implemented by physics integration crates (or their tests) to run the conformance suite.
+///
+/// The suite creates entities with a `Transform` and calls the `insert_*` methods to turn them
+/// into physics objects.
+pub trait TnuaConformanceBackend {
+    /// The backend's [`TnuaSpatialExt`] system parameter, used for checking the data the backend
+    /// computes on demand.
+    type SpatialExt: 'static + for<'w, 's> SystemParam<Item<'w, 's>: TnuaSpatialExt>;
+
+    /// Add the physics engine and the integration plugin to the app.
+    ///
+    /// The app already has `MinimalPlugins`, `TransformPlugin` and `HierarchyPlugin`, and its time
+    /// advances by [`FRAME_DURATION`] on every update. The backend should configure the physics
+    /// engine so that it steps exactly once per update.
+    ///
+    /// Returns the schedule the integration plugin's systems were registered in.
+    fn setup_app(app: &mut App) -> InternedScheduleLabel;
+
+    /// Make the entity a dynamic rigid body with a ball collider.
+    ///
+    /// The body must not be able to rotate, and must not be affected by friction or damping.
+    fn insert_character_body(entity: &mut EntityWorldMut, radius: Float);
+
+    /// Make the entity a static collider shaped as a cuboid.
+    fn insert_static_cuboid(entity: &mut EntityWorldMut, half_extents: Vector3);
+
+    /// Make the entity a static collider shaped as a cuboid, that does not physically interact
+    /// with the colliders created by [`insert_character_body`](Self::insert_character_body).
+    ///
+    /// This is how ghost platforms are usually configured.
+    fn insert_passable_cuboid(entity: &mut EntityWorldMut, half_extents: Vector3);
+}
+
+/// Run all the checks in this module.
+pub fn run_all_checks<B: TnuaConformanceBackend>() {
+    check_rigid_body_tracker::<B>();
+    check_tnua_gravity::<B>();
+    check_motor::<B>();
+    check_sensor_hits_first_tangible_collider::<B>();
+    check_sensor_ignores_owner::<B>();
+    check_sensor_skips_not_platform::<B>();
+    check_sensor_skips_passable_colliders::<B>();
+    check_ghost_sensor::<B>();
+    check_subservient_sensor::<B>();
+    check_predictive_sensor_cast::<B>();
+    check_obstacle_radar::<B>();
+    check_obstacle_radar_shapes::<B>();
+    check_toggle::<B>();
+}
+
+struct ConformanceScene {
+    app: App,
+}
+
+impl ConformanceScene {
+    fn new<B: TnuaConformanceBackend>() -> Self {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            FRAME_DURATION,
+        )));
+        app.insert_resource(Time::<Fixed>::from_seconds(FRAME_DURATION));
+        let schedule = B::setup_app(&mut app);
+        app.configure_sets(
+            schedule,
+            (
+                TnuaPipelineStages::Sensors,
+                TnuaPipelineStages::SubservientSensors,
+                TnuaPipelineStages::Logic,
+                TnuaPipelineStages::Motors,
+            )
+                .chain()
+                .in_set(TnuaSystemSet),
+        );
+        Self { app }
+    }
+
+    fn spawn(&mut self, position: Vector3) -> EntityWorldMut<'_> {
+        self.app
+            .world_mut()
+            .spawn(Transform::from_translation(position.f32()))
+    }
+
+    fn spawn_ground<B: TnuaConformanceBackend>(&mut self, top: Float) -> Entity {
+        let mut cmd = self.spawn(Vector3::new(0.0, top - 0.5, 0.0));
+        B::insert_static_cuboid(&mut cmd, Vector3::new(10.0, 0.5, 10.0));
+        cmd.id()
+    }
+
+    fn spawn_character<B: TnuaConformanceBackend>(&mut self, position: Vector3) -> Entity {
+        let mut cmd = self.spawn(position);
+        B::insert_character_body(&mut cmd, CHARACTER_RADIUS);
+        cmd.insert((
+            TnuaRigidBodyTracker::default(),
+            TnuaMotor::default(),
+            TnuaProximitySensor {
+                cast_origin: Vector3::ZERO,
+                cast_direction: -Dir3::Y,
+                cast_range: 10.0,
+                output: None,
+            },
+        ));
+        cmd.id()
+    }
+
+    /// Spawn a character with zero gravity, so that measurements are not affected by falling.
+    fn spawn_floating_character<B: TnuaConformanceBackend>(&mut self, position: Vector3) -> Entity {
+        let character = self.spawn_character::<B>(position);
+        self.app
+            .world_mut()
+            .entity_mut(character)
+            .insert(TnuaGravity(Vector3::ZERO));
+        character
+    }
+
+    /// Run enough frames for the physics engine to register changes in the scene.
+    fn settle(&mut self) {
+        self.run_frames(SETTLE_FRAMES);
+    }
+
+    fn run_frames(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    fn get<C: Component>(&self, entity: Entity) -> &C {
+        self.app
+            .world()
+            .get::<C>(entity)
+            .unwrap_or_else(|| panic!("Entity {entity} has no {}", std::any::type_name::<C>()))
+    }
+
+    fn get_mut<C: Component>(&mut self, entity: Entity) -> Mut<'_, C> {
+        self.app
+            .world_mut()
+            .get_mut::<C>(entity)
+            .unwrap_or_else(|| panic!("Entity {entity} has no {}", std::any::type_name::<C>()))
+    }
+
+    /// Return the entity and proximity detected by the sensor.
+    fn sensor_hit(&self, entity: Entity) -> Option<(Entity, Float)> {
+        let output = self.get::<TnuaProximitySensor>(entity).output.as_ref()?;
+        Some((output.entity, output.proximity))
+    }
+}
+
+fn assert_close(what: &str, actual: Float, expected: Float) {
+    assert!(
+        (actual - expected).abs() <= TOLERANCE,
+        "{what} is {actual}, expected {expected}",
+    );
+}
+
+/// The backend must update [`TnuaRigidBodyTracker`] with the character's kinematic status and
+/// with the global gravity.
+pub fn check_rigid_body_tracker<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let character = scene.spawn_character::<B>(Vector3::new(1.0, 5.0, 0.0));
+    scene.settle();
+
+    let tracker = scene.get::<TnuaRigidBodyTracker>(character);
+    assert_close("Tracked X position", tracker.translation.x, 1.0);
+    assert!(
+        tracker.translation.y < 5.0,
+        "Character should fall under the global gravity, but it is at {}",
+        tracker.translation,
+    );
+    assert!(
+        tracker.gravity.y < 0.0,
+        "Tracked gravity should be the global gravity, but it is {}",
+        tracker.gravity,
+    );
+    assert!(
+        tracker.velocity.y < 0.0,
+        "Tracked velocity should point down when falling, but it is {}",
+        tracker.velocity,
+    );
+}
+
+/// When [`TnuaGravity`] is set, the backend must report it in the [`TnuaRigidBodyTracker`] and
+/// apply it instead of the global gravity.
+pub fn check_tnua_gravity<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 5.0, 0.0));
+    scene.get_mut::<TnuaGravity>(character).0 = Vector3::new(3.0, 0.0, 0.0);
+    scene.run_frames(30);
+
+    let tracker = scene.get::<TnuaRigidBodyTracker>(character);
+    assert_close("Tracked gravity X", tracker.gravity.x, 3.0);
+    assert_close("Tracked gravity Y", tracker.gravity.y, 0.0);
+    assert!(
+        0.0 < tracker.velocity.x,
+        "TnuaGravity should accelerate the character, but its velocity is {}",
+        tracker.velocity,
+    );
+    assert_close(
+        "Vertical velocity with the global gravity nullified",
+        tracker.velocity.y,
+        0.0,
+    );
+}
+
+/// The backend must apply the boosts and accelerations from the [`TnuaMotor`].
+pub fn check_motor<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 5.0, 0.0));
+    scene.settle();
+
+    scene.get_mut::<TnuaMotor>(character).lin = TnuaVelChange::boost(Vector3::new(2.0, 0.0, 0.0));
+    scene.run_frames(1);
+    scene.get_mut::<TnuaMotor>(character).lin = TnuaVelChange::default();
+    scene.settle();
+    let velocity = scene.get::<TnuaRigidBodyTracker>(character).velocity;
+    assert_close("Velocity after a boost", velocity.x, 2.0);
+
+    scene.get_mut::<TnuaMotor>(character).lin =
+        TnuaVelChange::acceleration(Vector3::new(-4.0, 0.0, 0.0));
+    scene.run_frames(60);
+    let velocity = scene.get::<TnuaRigidBodyTracker>(character).velocity;
+    assert!(
+        velocity.x < 0.0,
+        "Motor acceleration should have reversed the velocity, but it is {velocity}",
+    );
+}
+
+/// The sensor must detect the first tangible collider along its cast.
+pub fn check_sensor_hits_first_tangible_collider<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let ground = scene.spawn_ground::<B>(0.0);
+    let mut cmd = scene.spawn(Vector3::new(0.0, 2.5, 0.0));
+    B::insert_static_cuboid(&mut cmd, Vector3::new(1.0, 0.5, 1.0));
+    let platform = cmd.id();
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 5.0, 0.0));
+    scene.settle();
+
+    let Some((hit_entity, proximity)) = scene.sensor_hit(character) else {
+        panic!("Sensor did not detect anything");
+    };
+    assert_eq!(
+        hit_entity, platform,
+        "Sensor should detect the nearest collider"
+    );
+    assert_close("Proximity to the platform", proximity, 2.0);
+
+    scene.app.world_mut().despawn(platform);
+    scene.settle();
+    let Some((hit_entity, proximity)) = scene.sensor_hit(character) else {
+        panic!("Sensor did not detect the ground after the platform was removed");
+    };
+    assert_eq!(hit_entity, ground);
+    assert_close("Proximity to the ground", proximity, 5.0);
+
+    scene.get_mut::<TnuaProximitySensor>(character).cast_range = 4.0;
+    scene.settle();
+    assert_eq!(
+        scene.sensor_hit(character),
+        None,
+        "Sensor should not detect colliders beyond its cast range",
+    );
+}
+
+/// The sensor must ignore the collider of its owner entity, even though its cast starts inside
+/// it.
+pub fn check_sensor_ignores_owner<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let ground = scene.spawn_ground::<B>(0.0);
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 3.0, 0.0));
+    scene.get_mut::<TnuaProximitySensor>(character).cast_origin =
+        Vector3::new(0.0, CHARACTER_RADIUS * 0.5, 0.0);
+    scene.settle();
+
+    assert_eq!(
+        scene.sensor_hit(character).map(|(entity, _)| entity),
+        Some(ground),
+        "Sensor should skip its owner's collider",
+    );
+}
+
+/// The sensor must skip colliders marked with [`TnuaNotPlatform`].
+pub fn check_sensor_skips_not_platform<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let ground = scene.spawn_ground::<B>(0.0);
+    let mut cmd = scene.spawn(Vector3::new(0.0, 2.5, 0.0));
+    B::insert_static_cuboid(&mut cmd, Vector3::new(1.0, 0.5, 1.0));
+    cmd.insert(TnuaNotPlatform);
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 5.0, 0.0));
+    scene.settle();
+
+    assert_eq!(
+        scene.sensor_hit(character).map(|(entity, _)| entity),
+        Some(ground),
+        "Sensor should skip TnuaNotPlatform colliders",
+    );
+}
+
+/// The sensor must skip colliders that do not physically interact with the character's collider.
+pub fn check_sensor_skips_passable_colliders<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let ground = scene.spawn_ground::<B>(0.0);
+    let mut cmd = scene.spawn(Vector3::new(0.0, 2.5, 0.0));
+    B::insert_passable_cuboid(&mut cmd, Vector3::new(1.0, 0.5, 1.0));
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 5.0, 0.0));
+    scene.settle();
+
+    assert_eq!(
+        scene.sensor_hit(character).map(|(entity, _)| entity),
+        Some(ground),
+        "Sensor should skip colliders that do not interact with the character",
+    );
+}
+
+/// With a [`TnuaGhostSensor`], the backend must report the [`TnuaGhostPlatform`] colliders found
+/// before the first tangible collider.
+pub fn check_ghost_sensor<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let ground = scene.spawn_ground::<B>(0.0);
+    let mut ghost_platforms = Vec::new();
+    for height in [1.5, 3.0] {
+        let mut cmd = scene.spawn(Vector3::new(0.0, height - 0.25, 0.0));
+        B::insert_passable_cuboid(&mut cmd, Vector3::new(1.0, 0.25, 1.0));
+        cmd.insert(TnuaGhostPlatform);
+        ghost_platforms.push(cmd.id());
+    }
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 5.0, 0.0));
+    scene
+        .app
+        .world_mut()
+        .entity_mut(character)
+        .insert(TnuaGhostSensor::default());
+    scene.settle();
+
+    assert_eq!(
+        scene.sensor_hit(character).map(|(entity, _)| entity),
+        Some(ground),
+        "Sensor output should be the tangible collider below the ghost platforms",
+    );
+    let ghost_sensor = scene.get::<TnuaGhostSensor>(character);
+    let detected = ghost_sensor
+        .iter()
+        .map(|output| output.entity)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        detected,
+        [ghost_platforms[1], ghost_platforms[0]],
+        "Ghost sensor should contain all the ghost platforms, nearest first",
+    );
+    for output in ghost_sensor.iter() {
+        assert!(
+            output.proximity < 5.0,
+            "Ghost platform {} was detected at proximity {}, beyond the ground",
+            output.entity,
+            output.proximity,
+        );
+    }
+}
+
+/// A sensor with [`TnuaSubservientSensor`] must ignore the collider of its owner entity rather
+/// than of the entity it is attached to.
+pub fn check_subservient_sensor<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let ground = scene.spawn_ground::<B>(0.0);
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 3.0, 0.0));
+    let sensor = scene
+        .app
+        .world_mut()
+        .spawn((
+            Transform::default(),
+            TnuaSubservientSensor {
+                owner_entity: character,
+            },
+            TnuaProximitySensor {
+                cast_origin: Vector3::new(0.0, CHARACTER_RADIUS, 0.0),
+                cast_direction: -Dir3::Y,
+                cast_range: 10.0,
+                output: None,
+            },
+        ))
+        .set_parent(character)
+        .id();
+    scene.settle();
+
+    let Some((hit_entity, proximity)) = scene.sensor_hit(sensor) else {
+        panic!("Subservient sensor did not detect anything");
+    };
+    assert_eq!(
+        hit_entity, ground,
+        "Subservient sensor should skip its owner's collider",
+    );
+    assert_close(
+        "Proximity of the subservient sensor",
+        proximity,
+        3.0 + CHARACTER_RADIUS,
+    );
+}
+
+/// With [`TnuaPredictiveSensorCast`], the sensor must detect colliders beyond its cast range when
+/// the character moves toward them fast enough to reach them during the frame.
+pub fn check_predictive_sensor_cast<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let ground = scene.spawn_ground::<B>(0.0);
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 8.0, 0.0));
+    scene
+        .app
+        .world_mut()
+        .entity_mut(character)
+        .insert(TnuaPredictiveSensorCast);
+    scene.get_mut::<TnuaProximitySensor>(character).cast_range = 1.0;
+    scene.settle();
+    assert_eq!(scene.sensor_hit(character), None);
+
+    // Fall 2.0 units per frame.
+    let fall_speed = 2.0 / FRAME_DURATION as Float;
+    scene.get_mut::<TnuaMotor>(character).lin =
+        TnuaVelChange::boost(Vector3::new(0.0, -fall_speed, 0.0));
+    scene.run_frames(1);
+    scene.get_mut::<TnuaMotor>(character).lin = TnuaVelChange::default();
+
+    for _ in 0..4 {
+        scene.run_frames(1);
+        if let Some((hit_entity, proximity)) = scene.sensor_hit(character) {
+            assert_eq!(hit_entity, ground);
+            assert!(
+                1.0 < proximity,
+                "Sensor should have detected the ground before it got into the cast range, but \
+                 it was detected at proximity {proximity}",
+            );
+            return;
+        }
+    }
+    panic!("Sensor did not detect the ground while falling toward it");
+}
+
+/// The backend must feed [`TnuaObstacleRadar`] with the character's data and mark the entities
+/// within its range.
+pub fn check_obstacle_radar<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let mut near_obstacle = scene.spawn(Vector3::new(1.5, 5.0, 0.0));
+    B::insert_static_cuboid(&mut near_obstacle, Vector3::new(0.5, 0.5, 0.5));
+    let near_obstacle = near_obstacle.id();
+    let mut far_obstacle = scene.spawn(Vector3::new(8.0, 5.0, 0.0));
+    B::insert_static_cuboid(&mut far_obstacle, Vector3::new(0.5, 0.5, 0.5));
+    let far_obstacle = far_obstacle.id();
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 5.0, 0.0));
+    scene.get_mut::<TnuaGravity>(character).0 = Vector3::new(0.0, -1e-3, 0.0);
+    scene
+        .app
+        .world_mut()
+        .entity_mut(character)
+        .insert(TnuaObstacleRadar::new(2.0, 3.0));
+    scene.settle();
+
+    let radar = scene.get::<TnuaObstacleRadar>(character);
+    assert_eq!(radar.tracked_entity(), character);
+    assert_close("Radar tracked X", radar.tracked_position().x, 0.0);
+    assert_close("Radar tracked Y", radar.tracked_position().y, 5.0);
+    assert!(
+        0.99 < radar.up_direction().adjust_precision().y,
+        "Radar up direction should be opposite to the gravity, but it is {:?}",
+        radar.up_direction(),
+    );
+    assert!(
+        radar.has_blip(near_obstacle),
+        "Radar should detect the obstacle within its range",
+    );
+    assert!(
+        !radar.has_blip(far_obstacle),
+        "Radar should not detect the obstacle outside its range",
+    );
+    assert!(
+        !radar.has_blip(character),
+        "Radar should not detect its own character",
+    );
+
+    let mut system_state =
+        SystemState::<(B::SpatialExt, Query<&TnuaObstacleRadar>)>::new(scene.app.world_mut());
+    let (spatial_ext, radars_query) = system_state.get_mut(scene.app.world_mut());
+    let radar = radars_query.get(character).unwrap();
+    let blip_data = radar
+        .blip_data(near_obstacle, &spatial_ext)
+        .expect("Radar should compute the data of the detected obstacle");
+    let closest_point = blip_data
+        .closest_point
+        .outside()
+        .expect("Radar position should be outside the obstacle");
+    assert_close("Blip closest point X", closest_point.x, 1.0);
+    assert_close("Blip closest point Y", closest_point.y, 5.0);
+    assert_close("Blip normal X", blip_data.normal.x, -1.0);
+    assert_close("Blip distance", blip_data.distance, 1.0);
+}
+
+/// The backend must scan the area defined by [`TnuaObstacleRadar::shape`].
+pub fn check_obstacle_radar_shapes<B: TnuaConformanceBackend>() {
+    let check = |shape: TnuaObstacleRadarShape, inside: Vector3, outside: Vector3| {
+        let mut scene = ConformanceScene::new::<B>();
+        let mut inside_obstacle = scene.spawn(inside);
+        B::insert_static_cuboid(&mut inside_obstacle, Vector3::new(0.5, 0.5, 0.5));
+        let inside_obstacle = inside_obstacle.id();
+        let mut outside_obstacle = scene.spawn(outside);
+        B::insert_static_cuboid(&mut outside_obstacle, Vector3::new(0.5, 0.5, 0.5));
+        let outside_obstacle = outside_obstacle.id();
+        let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 5.0, 0.0));
+        scene
+            .app
+            .world_mut()
+            .entity_mut(character)
+            .insert(TnuaObstacleRadar::from_shape(shape));
+        scene.settle();
+
+        let radar = scene.get::<TnuaObstacleRadar>(character);
+        assert!(
+            radar.has_blip(inside_obstacle),
+            "Radar with shape {shape:?} should detect the obstacle at {inside}",
+        );
+        assert!(
+            !radar.has_blip(outside_obstacle),
+            "Radar with shape {shape:?} should not detect the obstacle at {outside}",
+        );
+    };
+
+    check(
+        TnuaObstacleRadarShape::Cylinder {
+            radius: 2.0,
+            height: 1.0,
+        },
+        Vector3::new(1.5, 5.0, 0.0),
+        Vector3::new(0.0, 7.0, 0.0),
+    );
+    check(
+        TnuaObstacleRadarShape::Sphere { radius: 2.0 },
+        Vector3::new(1.5, 5.0, 0.0),
+        Vector3::new(3.0, 5.0, 0.0),
+    );
+    check(
+        TnuaObstacleRadarShape::Cuboid {
+            half_extents: Vector3::new(3.0, 0.5, 3.0),
+        },
+        Vector3::new(3.0, 5.0, 0.0),
+        Vector3::new(0.0, 7.0, 0.0),
+    );
+    check(
+        TnuaObstacleRadarShape::Cone {
+            radius: 1.0,
+            length: 3.0,
+            direction: Dir3::X,
+        },
+        Vector3::new(2.0, 5.0, 0.0),
+        Vector3::new(-2.0, 5.0, 0.0),
+    );
+}
+
+/// The backend must respect [`TnuaToggle`].
+pub fn check_toggle<B: TnuaConformanceBackend>() {
+    let mut scene = ConformanceScene::new::<B>();
+    let ground = scene.spawn_ground::<B>(0.0);
+    let character = scene.spawn_floating_character::<B>(Vector3::new(0.0, 5.0, 0.0));
+    scene
+        .app
+        .world_mut()
+        .entity_mut(character)
+        .insert(TnuaToggle::Disabled);
+    scene.get_mut::<TnuaMotor>(character).lin = TnuaVelChange::boost(Vector3::new(2.0, 0.0, 0.0));
+    scene.settle();
+    assert_eq!(
+        scene.sensor_hit(character),
+        None,
+        "Sensor should not be updated when Tnua is disabled",
+    );
+
+    *scene.get_mut::<TnuaToggle>(character) = TnuaToggle::SenseOnly;
+    scene.settle();
+    assert_eq!(
+        scene.sensor_hit(character).map(|(entity, _)| entity),
+        Some(ground),
+        "Sensor should be updated in SenseOnly mode",
+    );
+    let velocity = scene.get::<TnuaRigidBodyTracker>(character).velocity;
+    assert_close("Velocity in SenseOnly mode", velocity.x, 0.0);
+
+    *scene.get_mut::<TnuaToggle>(character) = TnuaToggle::Enabled;
+    scene.run_frames(1);
+    scene.get_mut::<TnuaMotor>(character).lin = TnuaVelChange::default();
+    scene.settle();
+    let velocity = scene.get::<TnuaRigidBodyTracker>(character).velocity;
+    assert_close("Velocity after re-enabling", velocity.x, 2.0);
+}
@@ -86,6 +86,34 @@ impl Default for TnuaProximitySensor {
     }
 }
 
+impl TnuaProximitySensor {
+    /// The cast range, extended by the distance the sensor is expected to travel in the cast
+    /// direction during the current frame.
+    ///
+    /// Physics backends should use this instead of [`cast_range`](Self::cast_range) for sensors
+    /// marked with [`TnuaPredictiveSensorCast`].
+    pub fn predictive_cast_range(&self, velocity: Vector3, frame_duration: Float) -> Float {
+        self.cast_range
+            + velocity
+                .dot(self.cast_direction.adjust_precision())
+                .max(0.0)
+                * frame_duration
+    }
+}
+
+/// Add this to a [`TnuaProximitySensor`] entity to extend its cast range by the distance it is
+/// expected to travel in the cast direction during the current frame.
+///
+/// Without it, a character falling fast enough can move from above the sensor's range to below
+/// the ground within a single frame, never getting the chance to detect the ground in time to
+/// brake. With it, the sensor detects the ground a frame earlier - which `TnuaBuiltinWalk` can
+/// use to start braking before it reaches the ground (see its `landing_prediction` field).
+///
+/// The velocity is taken from the physics engine's velocity of the sensor entity. Subservient
+/// sensors without a rigid body of their own are not affected.
+#[derive(Component, Default, Debug)]
+pub struct TnuaPredictiveSensorCast;
+
 /// Information from [`TnuaProximitySensor`] that have detected another collider.
 #[derive(Debug, Clone)]
 pub struct TnuaProximitySensorOutput {
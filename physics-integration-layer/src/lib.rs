@@ -34,12 +34,17 @@
 //!       `Tnua<physics-engine-name>SensorShape`.
 //!     * The detection should skip entities marked with the
 //!       [`TnuaNotPlatform`](data_for_backends::TnuaNotPlatform) component.
+//!     * If the sensor entity has the
+//!       [`TnuaPredictiveSensorCast`](data_for_backends::TnuaPredictiveSensorCast) component, the
+//!       cast range should be taken from
+//!       [`predictive_cast_range`](data_for_backends::TnuaProximitySensor::predictive_cast_range)
+//!       instead of from the `cast_range` field.
 //!   * [`TnuaObstacleRadar`](data_for_backends::TnuaObstacleRadar) with all the entities within
-//!     proximity (defined by `radius` and `height` fields on the radar component). The system must
-//!     first call the
+//!     proximity (defined by the radar's [`shape`](obstacle_radar::TnuaObstacleRadarShape)) that
+//!     pass the character's collision filters. The system must first call the
 //!     [`pre_marking_update`](data_for_backends::TnuaObstacleRadar::pre_marking_update) method,
-//!     and only then call [`mark_seen`](data_for_backends::TnuaObstacleRadar::mark_seen) on each
-//!     entity still within range in the current frame.
+//!     then call [`mark_seen`](data_for_backends::TnuaObstacleRadar::mark_seen) on each entity
+//!     still within range in the current frame.
 //!
 //!   The integration crate may update all these components in one system or multiple systems as it
 //!   sees fit.
@@ -87,8 +92,13 @@
 //! body and a collider, so they should not go in that bundle, but if the crate needs things users
 //! rarely think about - for example, bevy_rapier's `ReadMassProperties` - then these components
 //! should go in that bundle.
+//!
+//! Integration crates can check that they fulfill these obligations with the suite in the
+//! `conformance` module (requires the `conformance` feature).
 use bevy::prelude::*;
 
+#[cfg(feature = "conformance")]
+pub mod conformance;
 pub mod data_for_backends;
 pub mod math;
 pub mod obstacle_radar;
@@ -1,32 +1,68 @@
+use std::sync::OnceLock;
+
 use bevy::{prelude::*, utils::HashMap};
 
 use crate::math::{Float, Vector3};
+use crate::spatial_ext::{TnuaPointProjectionResult, TnuaSpatialExt};
 
 /// Add this to a character entity to detect obstacles around it.
 ///
 /// Obstacles can be used for environment movement actions like climbing and wall-jumping.
 ///
-/// This component stores rather limited data - only the detected entities and nothing about their
-/// form or position. See `TnuaRadarLens` in the main Tnua crate, which wraps this with a
-/// [`TnuaSpatialExt`](crate::spatial_ext::TnuaSpatialExt) to provide many helper methods for
-/// running more queries on the detected obstacles.
+/// The radar only detects colliders that can interact with the character's own collision
+/// layers/groups.
+///
+/// This component stores the detected entities. Some basic contact data ([`TnuaRadarBlipData`])
+/// for each of them is computed on demand (at most once per frame) by
+/// [`blip_data`](Self::blip_data). See `TnuaRadarLens` in the main Tnua crate, which wraps this
+/// with a [`TnuaSpatialExt`] to provide many helper methods for running more queries on the
+/// detected obstacles.
 #[derive(Component)]
 pub struct TnuaObstacleRadar {
-    /// The radius of the radar's cylinder.
-    pub radius: Float,
-    /// The height of the radar's cylinder.
-    pub height: Float,
+    /// The shape of the radar.
+    pub shape: TnuaObstacleRadarShape,
     tracked_entity: Entity,
     tracked_position: Vector3,
     up_direction: Dir3,
-    blips: HashMap<Entity, BlipStatus>,
+    blips: HashMap<Entity, BlipEntry>,
+}
+
+/// The shape of the area a [`TnuaObstacleRadar`] scans for obstacles.
+#[derive(Debug, Clone, Copy)]
+pub enum TnuaObstacleRadarShape {
+    /// A cylinder around the character, aligned with the up direction (the opposite of the
+    /// gravity). In 2D this is a rectangle.
+    Cylinder { radius: Float, height: Float },
+    /// A sphere around the character. In 2D this is a circle.
+    Sphere { radius: Float },
+    /// A box around the character, rotated with it. In 2D this is a rectangle and the Z extent is
+    /// ignored.
+    Cuboid { half_extents: Vector3 },
+    /// A cone with its apex at the character's position, pointing in the direction the character
+    /// is facing. In 2D this is a triangle.
+    Cone {
+        /// The radius of the cone's base.
+        radius: Float,
+        /// The distance from the apex to the base.
+        length: Float,
+        /// The direction of the cone, in the character's local frame (so that it will follow the
+        /// character's rotation). For 3D characters this is usually [`Dir3::NEG_Z`].
+        ///
+        /// In 2D only the X and Y components are used, so the direction must not be parallel to
+        /// the Z axis. For 2D characters this is usually [`Dir3::X`].
+        direction: Dir3,
+    },
 }
 
 impl TnuaObstacleRadar {
+    /// Create a radar with a cylinder shape.
     pub fn new(radius: Float, height: Float) -> Self {
+        Self::from_shape(TnuaObstacleRadarShape::Cylinder { radius, height })
+    }
+
+    pub fn from_shape(shape: TnuaObstacleRadarShape) -> Self {
         Self {
-            radius,
-            height,
+            shape,
             tracked_entity: Entity::PLACEHOLDER,
             tracked_position: Vector3::NAN,
             up_direction: Dir3::Y,
@@ -46,10 +82,11 @@ impl TnuaObstacleRadar {
         self.tracked_entity = tracked_entity;
         self.tracked_position = tracked_position;
         self.up_direction = up_direction;
-        self.blips.retain(|_, blip_status| match blip_status {
+        self.blips.retain(|_, blip| match blip.status {
             BlipStatus::Unseen => false,
             BlipStatus::Seen => {
-                *blip_status = BlipStatus::Unseen;
+                blip.status = BlipStatus::Unseen;
+                blip.data = OnceLock::new();
                 true
             }
         });
@@ -58,7 +95,13 @@ impl TnuaObstacleRadar {
     /// Physics integration crates should call this for each detected entity during each frame,
     /// after invoking [`pre_marking_update`](Self::pre_marking_update).
     pub fn mark_seen(&mut self, entity: Entity) {
-        self.blips.insert(entity, BlipStatus::Seen);
+        self.blips.insert(
+            entity,
+            BlipEntry {
+                status: BlipStatus::Seen,
+                data: OnceLock::new(),
+            },
+        );
     }
 
     /// Get the character entity who owns the radar (not the detected obstacle entities!)
@@ -86,9 +129,88 @@ impl TnuaObstacleRadar {
     pub fn has_blip(&self, entity: Entity) -> bool {
         self.blips.contains_key(&entity)
     }
+
+    /// Get the contact data of a blip.
+    ///
+    /// The data is computed the first time it is requested in each frame, and cached until the
+    /// next [`pre_marking_update`](Self::pre_marking_update).
+    ///
+    /// Returns `None` if the entity was not detected, or if its data could not be computed.
+    pub fn blip_data<X: TnuaSpatialExt>(
+        &self,
+        entity: Entity,
+        ext: &X,
+    ) -> Option<&TnuaRadarBlipData> {
+        self.blips
+            .get(&entity)?
+            .data
+            .get_or_init(|| {
+                let collider_data = ext.fetch_collider_data(entity)?;
+                Some(TnuaRadarBlipData::compute(
+                    ext,
+                    self.tracked_position,
+                    &collider_data,
+                ))
+            })
+            .as_ref()
+    }
+}
+
+struct BlipEntry {
+    status: BlipStatus,
+    data: OnceLock<Option<TnuaRadarBlipData>>,
 }
 
 pub enum BlipStatus {
     Unseen,
     Seen,
 }
+
+/// Contact data of a detected obstacle, relative to the position of the character that owns the
+/// [`TnuaObstacleRadar`].
+#[derive(Debug, Clone, Copy)]
+pub struct TnuaRadarBlipData {
+    /// The point on the obstacle's collider that's closest to the character's position.
+    pub closest_point: TnuaPointProjectionResult,
+    /// The normal of the obstacle's surface at the closest point.
+    ///
+    /// If the character's position is inside the collider, the normal is flipped. Will be zero if
+    /// it could not be determined.
+    pub normal: Vector3,
+    /// The distance between the character's position and the closest point.
+    pub distance: Float,
+}
+
+impl TnuaRadarBlipData {
+    /// Compute the blip data of a collider relative to `origin`.
+    pub fn compute<X: TnuaSpatialExt>(
+        ext: &X,
+        origin: Vector3,
+        collider_data: &X::ColliderData<'_>,
+    ) -> Self {
+        let closest_point = ext.project_point(origin, false, collider_data);
+
+        let get_normal = |closest_point: Vector3| -> Vector3 {
+            let Some(direction) = (closest_point - origin).try_normalize() else {
+                return Vector3::ZERO;
+            };
+            let Some((_, normal)) = ext.cast_ray(origin, direction, Float::INFINITY, collider_data)
+            else {
+                warn!("Unable to query normal to already-found closest point");
+                return Vector3::ZERO;
+            };
+            normal
+        };
+
+        let normal = match closest_point {
+            TnuaPointProjectionResult::Outside(closest_point) => get_normal(closest_point),
+            TnuaPointProjectionResult::Inside(closest_point) => -get_normal(closest_point),
+        };
+
+        Self {
+            closest_point,
+            normal,
+            distance: closest_point.get().distance(origin),
+        }
+    }
+}
@@ -41,6 +41,26 @@ pub trait TnuaSpatialExt {
     /// If the physics engine is detecting the collision but does not apply forces according to it,
     /// this method should return `false`.
     fn can_interact(&self, entity1: Entity, entity2: Entity) -> bool;
+
+    /// Get the mass and velocity of the dynamic rigid body the collider entity belongs to.
+    ///
+    /// Should return `None` if the collider does not belong to a dynamic rigid body (e.g. if it is
+    /// static or kinematic). The default implementation always returns `None`.
+    fn dynamic_body_data(&self, _entity: Entity) -> Option<TnuaDynamicBodyData> {
+        None
+    }
+}
+
+/// Information about a dynamic rigid body, returned by
+/// [`TnuaSpatialExt::dynamic_body_data`].
+#[derive(Debug, Copy, Clone)]
+pub struct TnuaDynamicBodyData {
+    /// The entity of the rigid body (which may be different than the collider entity).
+    pub body_entity: Entity,
+    /// The mass of the rigid body.
+    pub mass: Float,
+    /// The linear velocity of the rigid body.
+    pub linvel: Vector3,
 }
 
 #[derive(Debug, Copy, Clone)]
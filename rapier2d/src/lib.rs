@@ -7,6 +7,8 @@
 //! * Optionally: Add [`TnuaRapier2dSensorShape`] to the sensor entities. This means the entity of
 //!   the characters controlled by Tnua, but also other things like the entity generated by
 //!   `TnuaCrouchEnforcer`, that can be affected with a closure.
+//! * Optionally: Add [`TnuaRapier2dKinematicCharacter`] to the character entity to move it as a
+//!   kinematic body instead of a dynamic one.
 mod spatial_ext;
 
 use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
@@ -18,12 +20,15 @@ use bevy_rapier2d::rapier::prelude::InteractionGroups;
 
 use bevy_tnua_physics_integration_layer::data_for_backends::TnuaGhostSensor;
 use bevy_tnua_physics_integration_layer::data_for_backends::TnuaGravity;
+use bevy_tnua_physics_integration_layer::data_for_backends::TnuaPredictiveSensorCast;
 use bevy_tnua_physics_integration_layer::data_for_backends::TnuaToggle;
 use bevy_tnua_physics_integration_layer::data_for_backends::{TnuaGhostPlatform, TnuaNotPlatform};
 use bevy_tnua_physics_integration_layer::data_for_backends::{
     TnuaMotor, TnuaProximitySensor, TnuaProximitySensorOutput, TnuaRigidBodyTracker,
 };
-use bevy_tnua_physics_integration_layer::obstacle_radar::TnuaObstacleRadar;
+use bevy_tnua_physics_integration_layer::obstacle_radar::{
+    TnuaObstacleRadar, TnuaObstacleRadarShape,
+};
 use bevy_tnua_physics_integration_layer::subservient_sensors::TnuaSubservientSensor;
 use bevy_tnua_physics_integration_layer::TnuaPipelineStages;
 use bevy_tnua_physics_integration_layer::TnuaSystemSet;
@@ -73,7 +78,11 @@ impl Plugin for TnuaRapier2dPlugin {
         );
         app.add_systems(
             self.schedule,
-            apply_motors_system.in_set(TnuaPipelineStages::Motors),
+            (
+                apply_motors_system,
+                apply_motors_to_kinematic_characters_system,
+            )
+                .in_set(TnuaPipelineStages::Motors),
         );
         app.add_systems(
             Update,
@@ -110,6 +119,41 @@ pub struct TnuaRapier2dIOBundle {
 #[derive(Component)]
 pub struct TnuaRapier2dSensorShape(pub Collider);
 
+/// Add this component to a character entity to move it as a kinematic body.
+///
+/// Instead of applying the motor as forces on a dynamic rigid body, Tnua will integrate the motor
+/// and the gravity into the character's velocity itself, and resolve collisions by casting the
+/// character's [`Collider`] along its movement and sliding it along whatever it hits. The
+/// resolved movement is written to the [`Velocity`], and rapier moves the kinematic body
+/// accordingly.
+///
+/// The [`Collider`] can be on the character entity itself or on one of its direct children. If
+/// Tnua cannot find it, it will log a warning and leave the character in place.
+///
+/// This will make the entity a [`RigidBody::KinematicVelocityBased`] unless it is spawned with a
+/// different [`RigidBody`] component.
+///
+/// Note that kinematic characters are not pushed by other bodies, and only push dynamic bodies
+/// through the physics engine's contact resolution with kinematic bodies.
+#[derive(Component, Clone, Debug)]
+#[require(RigidBody(|| RigidBody::KinematicVelocityBased))]
+pub struct TnuaRapier2dKinematicCharacter {
+    /// The gap to keep between the character's collider and the surfaces it slides on.
+    pub collision_margin: f32,
+
+    /// The maximum number of times the movement can be deflected by collisions in a single frame.
+    pub max_slide_iterations: usize,
+}
+
+impl Default for TnuaRapier2dKinematicCharacter {
+    fn default() -> Self {
+        Self {
+            collision_margin: 0.01,
+            max_slide_iterations: 4,
+        }
+    }
+}
+
 fn update_rigid_body_trackers_system(
     rapier_config: Single<&RapierConfiguration>,
     mut query: Query<(
@@ -149,6 +193,7 @@ pub(crate) fn get_collider(
 
 #[allow(clippy::type_complexity)]
 fn update_proximity_sensors_system(
+    time: Res<Time>,
     rapier_context_query: Query<RapierContext>,
     mut query: Query<(
         Entity,
@@ -159,11 +204,14 @@ fn update_proximity_sensors_system(
         Option<&mut TnuaGhostSensor>,
         Option<&TnuaSubservientSensor>,
         Option<&TnuaToggle>,
+        &Velocity,
+        Has<TnuaPredictiveSensorCast>,
     )>,
     ghost_platforms_query: Query<(), With<TnuaGhostPlatform>>,
     not_platform_query: Query<(), With<TnuaNotPlatform>>,
     other_object_query_query: Query<(&GlobalTransform, &Velocity)>,
 ) {
+    let frame_duration = time.delta_secs();
     query.par_iter_mut().for_each(
         |(
             owner_entity,
@@ -174,12 +222,19 @@ fn update_proximity_sensors_system(
             mut ghost_sensor,
             subservient,
             tnua_toggle,
+            velocity,
+            predictive_cast,
         )| {
             match tnua_toggle.copied().unwrap_or_default() {
                 TnuaToggle::Disabled => return,
                 TnuaToggle::SenseOnly => {}
                 TnuaToggle::Enabled => {}
             }
+            let sensor_cast_range = if predictive_cast {
+                sensor.predictive_cast_range(velocity.linvel.extend(0.0), frame_duration)
+            } else {
+                sensor.cast_range
+            };
 
             let Ok(rapier_context) = rapier_context_query.get(rapier_context_entity_link.0) else {
                 return;
@@ -249,7 +304,7 @@ fn update_proximity_sensors_system(
                 };
                 let query_filter = query_filter.predicate(&predicate);
                 let cast_origin = cast_origin + cast_range_skip * *cast_direction;
-                let cast_range = sensor.cast_range - cast_range_skip;
+                let cast_range = sensor_cast_range - cast_range_skip;
                 if let Some(TnuaRapier2dSensorShape(shape)) = shape {
                     rapier_context
                         .query_pipeline
@@ -327,8 +382,8 @@ fn update_proximity_sensors_system(
                             // I hate having to do this so much, but without it it sometimes enters
                             // an infinte loop...
                             cast_range_skip = proximity
-                                + if sensor.cast_range.is_finite() && 0.0 < sensor.cast_range {
-                                    0.1 * sensor.cast_range
+                                + if sensor_cast_range.is_finite() && 0.0 < sensor_cast_range {
+                                    0.1 * sensor_cast_range
                                 } else {
                                     0.1
                                 };
@@ -387,12 +442,13 @@ fn update_obstacle_radars_system(
         &RapierContextEntityLink,
         &mut TnuaObstacleRadar,
         &GlobalTransform,
+        Option<&TnuaRigidBodyTracker>,
     )>,
 ) {
     if radars_query.is_empty() {
         return;
     }
-    for (radar_owner_entity, rapier_context_entity_link, mut radar, radar_transform) in
+    for (radar_owner_entity, rapier_context_entity_link, mut radar, radar_transform, tracker) in
         radars_query.iter_mut()
     {
         let Ok((rapier_context, rapier_config)) =
@@ -402,22 +458,64 @@ fn update_obstacle_radars_system(
         };
         let (_radar_scale, radar_rotation, radar_translation) =
             radar_transform.to_scale_rotation_translation();
-        radar.pre_marking_update(
-            radar_owner_entity,
-            radar_translation,
-            Dir3::new(rapier_config.gravity.extend(0.0)).unwrap_or(Dir3::Y),
-        );
+        let up_direction = Dir3::new(
+            -tracker
+                .map(|tracker| tracker.gravity)
+                .unwrap_or(rapier_config.gravity.extend(0.0)),
+        )
+        .unwrap_or(Dir3::Y);
+        radar.pre_marking_update(radar_owner_entity, radar_translation, up_direction);
+        let radar_angle = radar_rotation.to_euler(EulerRot::ZYX).0;
+        let (radar_collider, shape_rotation) = match radar.shape {
+            TnuaObstacleRadarShape::Cylinder { radius, height } => (
+                Collider::cuboid(radius, 0.5 * height),
+                Vec2::Y.angle_to(up_direction.truncate()),
+            ),
+            TnuaObstacleRadarShape::Sphere { radius } => (Collider::ball(radius), radar_angle),
+            TnuaObstacleRadarShape::Cuboid { half_extents } => (
+                Collider::cuboid(half_extents.x, half_extents.y),
+                radar_angle,
+            ),
+            TnuaObstacleRadarShape::Cone {
+                radius,
+                length,
+                direction,
+            } => {
+                let Some(direction) = (radar_rotation * *direction).truncate().try_normalize()
+                else {
+                    warn_once!(
+                        "Obstacle radar cone direction {direction:?} has no component in the XY plane"
+                    );
+                    continue;
+                };
+                let base_center = length * direction;
+                let base_offset = radius * direction.perp();
+                (
+                    Collider::triangle(
+                        Vec2::ZERO,
+                        base_center - base_offset,
+                        base_center + base_offset,
+                    ),
+                    0.0,
+                )
+            }
+        };
+        let mut query_filter = QueryFilter::new().exclude_rigid_body(radar_owner_entity);
+        if let Some(owner_collider) = get_collider(rapier_context.colliders, radar_owner_entity) {
+            let collision_groups = owner_collider.collision_groups();
+            query_filter.groups = Some(CollisionGroups {
+                memberships: Group::from_bits_truncate(collision_groups.memberships.bits()),
+                filters: Group::from_bits_truncate(collision_groups.filter.bits()),
+            });
+        }
         rapier_context.query_pipeline.intersections_with_shape(
             rapier_context.colliders,
             rapier_context.rigidbody_set,
             radar_translation.truncate(),
-            radar_rotation.to_euler(EulerRot::ZYX).0,
-            &Collider::cuboid(radar.radius, 0.5 * radar.height),
-            Default::default(),
+            shape_rotation,
+            &radar_collider,
+            query_filter,
             |obstacle_entity| {
-                if radar_owner_entity == obstacle_entity {
-                    return true;
-                }
                 radar.mark_seen(obstacle_entity);
                 true
             },
@@ -426,14 +524,17 @@ fn update_obstacle_radars_system(
 }
 
 fn apply_motors_system(
-    mut query: Query<(
-        &TnuaMotor,
-        &mut Velocity,
-        &ReadMassProperties,
-        &mut ExternalForce,
-        Option<&TnuaToggle>,
-        Option<&TnuaGravity>,
-    )>,
+    mut query: Query<
+        (
+            &TnuaMotor,
+            &mut Velocity,
+            &ReadMassProperties,
+            &mut ExternalForce,
+            Option<&TnuaToggle>,
+            Option<&TnuaGravity>,
+        ),
+        Without<TnuaRapier2dKinematicCharacter>,
+    >,
 ) {
     for (motor, mut velocity, mass_properties, mut external_force, tnua_toggle, tnua_gravity) in
         query.iter_mut()
@@ -463,3 +564,135 @@ fn apply_motors_system(
         }
     }
 }
+
+#[allow(clippy::type_complexity)]
+fn apply_motors_to_kinematic_characters_system(
+    time: Res<Time>,
+    rapier_world_query: Query<(RapierContext, &RapierConfiguration)>,
+    mut query: Query<(
+        Entity,
+        &TnuaRapier2dKinematicCharacter,
+        &RapierContextEntityLink,
+        &TnuaMotor,
+        Option<&Children>,
+        &mut Velocity,
+        Option<&TnuaToggle>,
+        Option<&TnuaGravity>,
+    )>,
+    colliders_query: Query<(&Collider, &GlobalTransform)>,
+) {
+    let frame_duration = time.delta_secs();
+    if frame_duration == 0.0 {
+        return;
+    }
+    for (
+        owner_entity,
+        kinematic_character,
+        rapier_context_entity_link,
+        motor,
+        children,
+        mut velocity,
+        tnua_toggle,
+        tnua_gravity,
+    ) in query.iter_mut()
+    {
+        match tnua_toggle.copied().unwrap_or_default() {
+            TnuaToggle::Disabled | TnuaToggle::SenseOnly => continue,
+            TnuaToggle::Enabled => {}
+        }
+        let Ok((rapier_context, rapier_config)) =
+            rapier_world_query.get(rapier_context_entity_link.0)
+        else {
+            continue;
+        };
+
+        // The collider may be on the character entity itself or on one of its children.
+        let Some((collider_entity, (collider, transform))) = std::iter::once(owner_entity)
+            .chain(children.into_iter().flatten().copied())
+            .find_map(|entity| Some((entity, colliders_query.get(entity).ok()?)))
+        else {
+            warn_once!(
+                "{owner_entity} has a TnuaRapier2dKinematicCharacter but no collider on it or on \
+                 its children, so Tnua cannot move it"
+            );
+            continue;
+        };
+
+        // Kinematic bodies are not affected by forces or by the physics engine's gravity, so the
+        // motor and the gravity are integrated into the velocity here.
+        let mut linvel = velocity.linvel;
+        if motor.lin.boost.is_finite() {
+            linvel += motor.lin.boost.truncate();
+        }
+        if motor.lin.acceleration.is_finite() {
+            linvel += motor.lin.acceleration.truncate() * frame_duration;
+        }
+        linvel += tnua_gravity
+            .map(|g| g.0.truncate())
+            .unwrap_or(rapier_config.gravity)
+            * frame_duration;
+
+        let mut query_filter = QueryFilter::new().exclude_rigid_body(owner_entity);
+        if let Some(owner_collider) = get_collider(rapier_context.colliders, collider_entity) {
+            let collision_groups = owner_collider.collision_groups();
+            query_filter.groups = Some(CollisionGroups {
+                memberships: Group::from_bits_truncate(collision_groups.memberships.bits()),
+                filters: Group::from_bits_truncate(collision_groups.filter.bits()),
+            });
+        }
+        let predicate = |other_entity: Entity| {
+            get_collider(rapier_context.colliders, other_entity)
+                .is_none_or(|other_collider| !other_collider.is_sensor())
+        };
+        let query_filter = query_filter.predicate(&predicate);
+
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+        let rotation = rotation.to_euler(EulerRot::ZYX).0;
+        let mut cast_origin = translation.truncate();
+        let mut remaining_displacement = linvel * frame_duration;
+        for _ in 0..kinematic_character.max_slide_iterations {
+            let Ok((direction, distance)) = Dir2::new_and_length(remaining_displacement) else {
+                break;
+            };
+            let Some((_, hit)) = rapier_context.query_pipeline.cast_shape(
+                rapier_context.colliders,
+                rapier_context.rigidbody_set,
+                cast_origin,
+                rotation,
+                *direction,
+                collider,
+                ShapeCastOptions {
+                    max_time_of_impact: distance + kinematic_character.collision_margin,
+                    target_distance: 0.0,
+                    stop_at_penetration: false,
+                    compute_impact_geometry_on_penetration: false,
+                },
+                query_filter,
+            ) else {
+                cast_origin += remaining_displacement;
+                remaining_displacement = Vec2::ZERO;
+                break;
+            };
+            let travel =
+                (hit.time_of_impact - kinematic_character.collision_margin).clamp(0.0, distance);
+            cast_origin += travel * *direction;
+            remaining_displacement -= travel * *direction;
+            let Some(normal) = hit
+                .details
+                .and_then(|details| details.normal1.try_normalize())
+            else {
+                break;
+            };
+            remaining_displacement -= remaining_displacement.dot(normal).min(0.0) * normal;
+        }
+
+        // Let the physics engine move the kinematic body to the resolved position.
+        velocity.linvel = (cast_origin - translation.truncate()) / frame_duration;
+        if motor.ang.boost.is_finite() {
+            velocity.angvel += motor.ang.boost.z;
+        }
+        if motor.ang.acceleration.is_finite() {
+            velocity.angvel += motor.ang.acceleration.z * frame_duration;
+        }
+    }
+}
@@ -2,7 +2,7 @@ use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_rapier2d::prelude::*;
 use bevy_tnua_physics_integration_layer::{
     math::{Float, Vector3},
-    spatial_ext::{TnuaPointProjectionResult, TnuaSpatialExt},
+    spatial_ext::{TnuaDynamicBodyData, TnuaPointProjectionResult, TnuaSpatialExt},
 };
 
 use crate::get_collider;
@@ -97,4 +97,21 @@ impl TnuaSpatialExt for TnuaSpatialExtRapier2d<'_, '_> {
             .test(collider2.collision_groups())
             && collider1.solver_groups().test(collider2.solver_groups())
     }
+
+    fn dynamic_body_data(&self, entity: Entity) -> Option<TnuaDynamicBodyData> {
+        let link = self.context_links_query.get(entity).ok()?;
+        let rapier_context = self.rapier_context_query.get(link.0).ok()?;
+        let body_handle = get_collider(rapier_context.colliders, entity)?.parent()?;
+        let body = rapier_context.rigidbody_set.bodies.get(body_handle)?;
+        if !body.is_dynamic() {
+            return None;
+        }
+        Some(TnuaDynamicBodyData {
+            body_entity: rapier_context
+                .rigidbody_set
+                .rigid_body_entity(body_handle)?,
+            mass: body.mass(),
+            linvel: Vec2::from(*body.linvel()).extend(0.0),
+        })
+    }
 }
@@ -0,0 +1,49 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use bevy_tnua_physics_integration_layer::conformance::{run_all_checks, TnuaConformanceBackend};
+use bevy_tnua_physics_integration_layer::math::{Float, Vector3};
+use bevy_tnua_rapier3d::{TnuaRapier3dPlugin, TnuaSpatialExtRapier3d};
+
+struct Rapier3dBackend;
+
+impl TnuaConformanceBackend for Rapier3dBackend {
+    type SpatialExt = TnuaSpatialExtRapier3d<'static, 'static>;
+
+    fn setup_app(app: &mut App) -> InternedScheduleLabel {
+        app.add_plugins((
+            RapierPhysicsPlugin::<NoUserData>::default().in_fixed_schedule(),
+            TnuaRapier3dPlugin::new(FixedUpdate),
+        ));
+        FixedUpdate.intern()
+    }
+
+    fn insert_character_body(entity: &mut EntityWorldMut, radius: Float) {
+        entity.insert((
+            RigidBody::Dynamic,
+            Collider::ball(radius),
+            LockedAxes::ROTATION_LOCKED,
+            Friction {
+                coefficient: 0.0,
+                combine_rule: CoefficientCombineRule::Min,
+            },
+        ));
+    }
+
+    fn insert_static_cuboid(entity: &mut EntityWorldMut, half_extents: Vector3) {
+        entity.insert((
+            RigidBody::Fixed,
+            Collider::cuboid(half_extents.x, half_extents.y, half_extents.z),
+        ));
+    }
+
+    fn insert_passable_cuboid(entity: &mut EntityWorldMut, half_extents: Vector3) {
+        Self::insert_static_cuboid(entity, half_extents);
+        entity.insert(SolverGroups::new(Group::GROUP_2, Group::NONE));
+    }
+}
+
+#[test]
+fn conformance() {
+    run_all_checks::<Rapier3dBackend>();
+}
@@ -0,0 +1,196 @@
+use crate::math::Float;
+use bevy::time::Stopwatch;
+
+use crate::builtins::charged_jump::apply_charging_float_offset;
+use crate::builtins::{TnuaBuiltinDash, TnuaBuiltinDashState};
+use crate::util::interpolate_sorted_entries;
+use crate::{
+    TnuaAction, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
+    TnuaActionLifecycleStatus, TnuaMotor,
+};
+
+/// A [`TnuaBuiltinDash`] that is charged while the action is fed and launched when it is no
+/// longer fed.
+///
+/// The distance of the dash is determined by how long it was charged, according to
+/// [`charge_curve`](Self::charge_curve). The direction of the dash is taken from the
+/// [`displacement`](TnuaBuiltinDash::displacement) of [`dash`](Self::dash) when the dash is
+/// launched, so the control system can keep updating it while charging.
+#[derive(Clone, Debug)]
+pub struct TnuaBuiltinChargedDash {
+    /// The dash to launch.
+    ///
+    /// The length of its [`displacement`](TnuaBuiltinDash::displacement) is replaced by the
+    /// distance from [`charge_curve`](Self::charge_curve). Its
+    /// [`allow_in_air`](TnuaBuiltinDash::allow_in_air) and
+    /// [`input_buffer_time`](TnuaBuiltinDash::input_buffer_time) apply to the start of the
+    /// charging.
+    pub dash: TnuaBuiltinDash,
+
+    /// The distance of the dash based on how long it was charged.
+    ///
+    /// Each entry is a duration, in seconds, and the distance of the dash when released after
+    /// being charged for that duration. The entries must be sorted by their durations, and the
+    /// distances between them are interpolated linearly. The charge is full when it reaches the
+    /// duration of the last entry.
+    ///
+    /// When empty, the dash will always use the [`displacement`](TnuaBuiltinDash::displacement)
+    /// of [`dash`](Self::dash) as is.
+    pub charge_curve: Vec<(Float, Float)>,
+
+    /// Launch the dash automatically once the charge is full, even if the action is still fed.
+    pub launch_when_fully_charged: bool,
+
+    /// Crouch while charging, by this offset from the regular float height.
+    ///
+    /// This field should typically have a negative value. Set to `0.0` to not crouch.
+    ///
+    /// Only works when [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk) is the
+    /// [basis](crate::TnuaBasis).
+    pub charging_float_offset: Float,
+}
+
+impl Default for TnuaBuiltinChargedDash {
+    fn default() -> Self {
+        Self {
+            dash: Default::default(),
+            charge_curve: Vec::new(),
+            launch_when_fully_charged: false,
+            charging_float_offset: 0.0,
+        }
+    }
+}
+
+impl TnuaBuiltinChargedDash {
+    /// The duration, in seconds, it takes to fully charge the dash.
+    pub fn full_charge_duration(&self) -> Float {
+        self.charge_curve
+            .last()
+            .map_or(0.0, |(duration, _)| duration.max(0.0))
+    }
+
+    /// The dash that will be launched after charging for the given duration.
+    pub fn dash_for_charge(&self, charged_for: Float) -> TnuaBuiltinDash {
+        let displacement =
+            if let Some(distance) = interpolate_sorted_entries(&self.charge_curve, charged_for) {
+                self.dash.displacement.normalize_or_zero() * distance
+            } else {
+                self.dash.displacement
+            };
+        TnuaBuiltinDash {
+            displacement,
+            ..self.dash.clone()
+        }
+    }
+
+    fn charge_progress(&self, charged_for: Float) -> Float {
+        let full_charge_duration = self.full_charge_duration();
+        if 0.0 < full_charge_duration {
+            (charged_for / full_charge_duration).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+impl TnuaAction for TnuaBuiltinChargedDash {
+    const NAME: &'static str = "TnuaBuiltinChargedDash";
+    type State = TnuaBuiltinChargedDashState;
+    const VIOLATES_COYOTE_TIME: bool = true;
+
+    fn initiation_decision(
+        &self,
+        ctx: TnuaActionContext,
+        being_fed_for: &Stopwatch,
+    ) -> TnuaActionInitiationDirective {
+        self.dash.initiation_decision(ctx, being_fed_for)
+    }
+
+    fn apply(
+        &self,
+        state: &mut Self::State,
+        ctx: TnuaActionContext,
+        lifecycle_status: TnuaActionLifecycleStatus,
+        motor: &mut TnuaMotor,
+    ) -> TnuaActionLifecycleDirective {
+        if let TnuaBuiltinChargedDashState::Charging {
+            charged_for,
+            progress,
+        } = state
+        {
+            let launch = match lifecycle_status {
+                TnuaActionLifecycleStatus::CancelledInto => {
+                    return TnuaActionLifecycleDirective::Finished;
+                }
+                TnuaActionLifecycleStatus::NoLongerFed => true,
+                TnuaActionLifecycleStatus::Initiated
+                | TnuaActionLifecycleStatus::CancelledFrom
+                | TnuaActionLifecycleStatus::StillFed => {
+                    *charged_for += ctx.frame_duration;
+                    self.launch_when_fully_charged && self.full_charge_duration() <= *charged_for
+                }
+            };
+            *progress = self.charge_progress(*charged_for);
+            if !launch {
+                apply_charging_float_offset(&ctx, motor, self.charging_float_offset);
+                return TnuaActionLifecycleDirective::StillActive;
+            }
+            if !self.dash.allow_in_air && ctx.basis.is_airborne() {
+                return TnuaActionLifecycleDirective::Finished;
+            }
+            *state = TnuaBuiltinChargedDashState::Launched {
+                charged_for: *charged_for,
+                dash_state: Default::default(),
+            };
+        }
+
+        let TnuaBuiltinChargedDashState::Launched {
+            charged_for,
+            dash_state,
+        } = state
+        else {
+            unreachable!("the charging state should have been handled");
+        };
+
+        self.dash_for_charge(*charged_for)
+            .apply(dash_state, ctx, lifecycle_status, motor)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TnuaBuiltinChargedDashState {
+    /// The dash is being charged.
+    Charging {
+        /// The duration, in seconds, the dash was charged for.
+        charged_for: Float,
+        /// How much of the full charge was reached, from `0.0` to `1.0`.
+        progress: Float,
+    },
+    /// The dash was launched.
+    Launched {
+        /// The duration, in seconds, the dash was charged for.
+        charged_for: Float,
+        /// The state of the launched dash.
+        dash_state: TnuaBuiltinDashState,
+    },
+}
+
+impl Default for TnuaBuiltinChargedDashState {
+    fn default() -> Self {
+        Self::Charging {
+            charged_for: 0.0,
+            progress: 0.0,
+        }
+    }
+}
+
+impl TnuaBuiltinChargedDashState {
+    /// How much of the full charge was reached, from `0.0` to `1.0`, or `None` if the dash was
+    /// already launched.
+    pub fn charge_progress(&self) -> Option<Float> {
+        match self {
+            Self::Charging { progress, .. } => Some(*progress),
+            Self::Launched { .. } => None,
+        }
+    }
+}
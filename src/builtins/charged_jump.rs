@@ -0,0 +1,225 @@
+use crate::math::{AdjustPrecision, Float};
+use bevy::time::Stopwatch;
+
+use crate::builtins::{TnuaBuiltinJump, TnuaBuiltinJumpState, TnuaBuiltinWalk};
+use crate::util::interpolate_sorted_entries;
+use crate::{
+    TnuaAction, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
+    TnuaActionLifecycleStatus, TnuaMotor,
+};
+
+/// A [`TnuaBuiltinJump`] that is charged while the action is fed and launched when it is no
+/// longer fed.
+///
+/// The height of the jump is determined by how long it was charged, according to
+/// [`charge_curve`](Self::charge_curve). Once launched, the jump is not shortened - the charge
+/// replaces the "hold longer to jump higher" mechanism of the regular jump.
+///
+/// While charging, the [`TnuaBuiltinWalk`] basis keeps working as usual (so the character can
+/// still move, unless the control system prevents it) and the character can optionally crouch by
+/// setting [`charging_float_offset`](Self::charging_float_offset).
+#[derive(Clone, Debug)]
+pub struct TnuaBuiltinChargedJump {
+    /// The jump to launch.
+    ///
+    /// Its [`height`](TnuaBuiltinJump::height) is used as the maximum height of the jump, and the
+    /// actual height is determined by [`charge_curve`](Self::charge_curve). Its
+    /// [`allow_in_air`](TnuaBuiltinJump::allow_in_air) and
+    /// [`input_buffer_time`](TnuaBuiltinJump::input_buffer_time) apply to the start of the
+    /// charging.
+    pub jump: TnuaBuiltinJump,
+
+    /// The height of the jump based on how long it was charged.
+    ///
+    /// Each entry is a duration, in seconds, and the height of the jump when released after being
+    /// charged for that duration. The entries must be sorted by their durations, and the heights
+    /// between them are interpolated linearly. The charge is full when it reaches the duration of
+    /// the last entry.
+    ///
+    /// When empty, the jump will always use the [`height`](TnuaBuiltinJump::height) of
+    /// [`jump`](Self::jump).
+    pub charge_curve: Vec<(Float, Float)>,
+
+    /// Launch the jump automatically once the charge is full, even if the action is still fed.
+    pub launch_when_fully_charged: bool,
+
+    /// Crouch while charging, by this offset from the regular float height.
+    ///
+    /// This field should typically have a negative value. Set to `0.0` to not crouch.
+    ///
+    /// Only works when [`TnuaBuiltinWalk`] is the [basis](crate::TnuaBasis).
+    pub charging_float_offset: Float,
+}
+
+impl Default for TnuaBuiltinChargedJump {
+    fn default() -> Self {
+        Self {
+            jump: Default::default(),
+            charge_curve: Vec::new(),
+            launch_when_fully_charged: false,
+            charging_float_offset: 0.0,
+        }
+    }
+}
+
+impl TnuaBuiltinChargedJump {
+    /// The duration, in seconds, it takes to fully charge the jump.
+    pub fn full_charge_duration(&self) -> Float {
+        self.charge_curve
+            .last()
+            .map_or(0.0, |(duration, _)| duration.max(0.0))
+    }
+
+    /// The jump that will be launched after charging for the given duration.
+    pub fn jump_for_charge(&self, charged_for: Float) -> TnuaBuiltinJump {
+        let height = interpolate_sorted_entries(&self.charge_curve, charged_for)
+            .map_or(self.jump.height, |height| height.min(self.jump.height));
+        TnuaBuiltinJump {
+            height,
+            reschedule_cooldown: None,
+            ..self.jump.clone()
+        }
+    }
+
+    fn charge_progress(&self, charged_for: Float) -> Float {
+        let full_charge_duration = self.full_charge_duration();
+        if 0.0 < full_charge_duration {
+            (charged_for / full_charge_duration).min(1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+impl TnuaAction for TnuaBuiltinChargedJump {
+    const NAME: &'static str = "TnuaBuiltinChargedJump";
+    type State = TnuaBuiltinChargedJumpState;
+    const VIOLATES_COYOTE_TIME: bool = true;
+
+    fn initiation_decision(
+        &self,
+        ctx: TnuaActionContext,
+        being_fed_for: &Stopwatch,
+    ) -> TnuaActionInitiationDirective {
+        self.jump.initiation_decision(ctx, being_fed_for)
+    }
+
+    fn apply(
+        &self,
+        state: &mut Self::State,
+        ctx: TnuaActionContext,
+        lifecycle_status: TnuaActionLifecycleStatus,
+        motor: &mut TnuaMotor,
+    ) -> TnuaActionLifecycleDirective {
+        if let TnuaBuiltinChargedJumpState::Charging {
+            charged_for,
+            progress,
+        } = state
+        {
+            let launch = match lifecycle_status {
+                TnuaActionLifecycleStatus::CancelledInto => {
+                    return TnuaActionLifecycleDirective::Finished;
+                }
+                TnuaActionLifecycleStatus::NoLongerFed => true,
+                TnuaActionLifecycleStatus::Initiated
+                | TnuaActionLifecycleStatus::CancelledFrom
+                | TnuaActionLifecycleStatus::StillFed => {
+                    *charged_for += ctx.frame_duration;
+                    self.launch_when_fully_charged && self.full_charge_duration() <= *charged_for
+                }
+            };
+            *progress = self.charge_progress(*charged_for);
+            if !launch {
+                apply_charging_float_offset(&ctx, motor, self.charging_float_offset);
+                return TnuaActionLifecycleDirective::StillActive;
+            }
+            if !self.jump.allow_in_air && ctx.basis.is_airborne() {
+                return TnuaActionLifecycleDirective::Finished;
+            }
+            *state = TnuaBuiltinChargedJumpState::Launched {
+                charged_for: *charged_for,
+                jump_state: Default::default(),
+            };
+        }
+
+        let TnuaBuiltinChargedJumpState::Launched {
+            charged_for,
+            jump_state,
+        } = state
+        else {
+            unreachable!("the charging state should have been handled");
+        };
+
+        // The charge determines the height of the jump, so the jump itself is considered fed for
+        // its entire duration.
+        let jump_lifecycle_status = if matches!(jump_state, TnuaBuiltinJumpState::NoJump) {
+            TnuaActionLifecycleStatus::Initiated
+        } else if matches!(lifecycle_status, TnuaActionLifecycleStatus::CancelledInto) {
+            TnuaActionLifecycleStatus::CancelledInto
+        } else {
+            TnuaActionLifecycleStatus::StillFed
+        };
+        self.jump_for_charge(*charged_for)
+            .apply(jump_state, ctx, jump_lifecycle_status, motor)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TnuaBuiltinChargedJumpState {
+    /// The jump is being charged.
+    Charging {
+        /// The duration, in seconds, the jump was charged for.
+        charged_for: Float,
+        /// How much of the full charge was reached, from `0.0` to `1.0`.
+        progress: Float,
+    },
+    /// The jump was launched.
+    Launched {
+        /// The duration, in seconds, the jump was charged for.
+        charged_for: Float,
+        /// The state of the launched jump.
+        jump_state: TnuaBuiltinJumpState,
+    },
+}
+
+impl Default for TnuaBuiltinChargedJumpState {
+    fn default() -> Self {
+        Self::Charging {
+            charged_for: 0.0,
+            progress: 0.0,
+        }
+    }
+}
+
+impl TnuaBuiltinChargedJumpState {
+    /// How much of the full charge was reached, from `0.0` to `1.0`, or `None` if the jump was
+    /// already launched.
+    pub fn charge_progress(&self) -> Option<Float> {
+        match self {
+            Self::Charging { progress, .. } => Some(*progress),
+            Self::Launched { .. } => None,
+        }
+    }
+}
+
+/// Use the spring of the [`TnuaBuiltinWalk`] basis to lower the character while charging.
+pub(super) fn apply_charging_float_offset(
+    ctx: &TnuaActionContext,
+    motor: &mut TnuaMotor,
+    float_offset: Float,
+) {
+    if float_offset == 0.0 {
+        return;
+    }
+    let Some((walk_basis, walk_state)) = ctx.concrete_basis::<TnuaBuiltinWalk>() else {
+        return;
+    };
+    let Some(sensor_output) = &ctx.proximity_sensor.output else {
+        return;
+    };
+    let spring_offset =
+        walk_basis.float_height - sensor_output.proximity.adjust_precision() + float_offset;
+    let up = ctx.up_direction.adjust_precision();
+    motor.lin.cancel_on_axis(up);
+    motor.lin += walk_basis.spring_force(walk_state, &ctx.as_basis_context(), spring_offset);
+}
@@ -1,6 +1,7 @@
 use bevy::prelude::*;
-use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, Float};
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Quaternion};
 
+use crate::builtins::TnuaBuiltinWalk;
 use crate::util::MotionHelper;
 use crate::TnuaActionContext;
 use crate::{
@@ -8,7 +9,15 @@ use crate::{
     TnuaActionLifecycleStatus, TnuaMotor,
 };
 
+/// How far below the top (in the up direction) the character can be and still be considered
+/// above it, for moving over it.
+const TOP_HEIGHT_TOLERANCE: Float = 0.01;
+
 /// An [action](TnuaAction) for climbing on things.
+///
+/// By default the action just keeps the character at a fixed position relative to the
+/// [`anchor`](Self::anchor) while moving it up and down. Set the [`variant`](Self::variant) to get
+/// behavior specific to [ladders](TnuaBuiltinClimbLadder) or [poles](TnuaBuiltinClimbPole).
 #[derive(Clone)]
 pub struct TnuaBuiltinClimb {
     /// The entity being climbed on.
@@ -57,12 +66,156 @@ pub struct TnuaBuiltinClimb {
     /// to find this point.
     pub hard_stop_down: Option<Vector3>,
 
+    /// A point on the walkable top of the climbed entity, for climbing over it.
+    ///
+    /// When the character climbs up and gets within [`top_out_reach`](Self::top_out_reach) below
+    /// this point, the action enters the [`ToppingOut`](TnuaBuiltinClimbState::ToppingOut) phase -
+    /// moving the character up and over onto the top, where it finishes. The character will stand
+    /// above this point at the [`float_height`](TnuaBuiltinWalk::float_height) of the basis.
+    ///
+    /// Tip: use [`top_surface`](crate::radar_lens::TnuaRadarBlipLens::top_surface) to find this
+    /// point.
+    pub top_surface: Option<Vector3>,
+
+    /// How far below the [`top_surface`](Self::top_surface) the character's center can be for
+    /// climbing over it.
+    pub top_out_reach: Float,
+
+    /// The speed of climbing over the [`top_surface`](Self::top_surface).
+    pub top_out_speed: Float,
+
     /// The direction used to initiate the climb.
     ///
     /// This field is not used by the action itself. It's purpose is to help user controller
     /// systems determine if the player input is a continuation of the motion used to initiate the
     /// climb, or if it's a motion for breaking from the climb.
     pub initiation_direction: Vector3,
+
+    /// Behavior specific to the kind of the climbed entity.
+    pub variant: TnuaBuiltinClimbVariant,
+}
+
+/// The kind of entity climbed by [`TnuaBuiltinClimb`].
+#[derive(Clone, Default)]
+pub enum TnuaBuiltinClimbVariant {
+    /// Use the fields of [`TnuaBuiltinClimb`] as is.
+    #[default]
+    Free,
+    /// Climb a ladder. See [`TnuaBuiltinClimbLadder`].
+    Ladder(TnuaBuiltinClimbLadder),
+    /// Climb a pole. See [`TnuaBuiltinClimbPole`].
+    Pole(TnuaBuiltinClimbPole),
+}
+
+/// Ladder specific behavior for [`TnuaBuiltinClimb`].
+///
+/// When climbing a ladder, [`desired_vec_to_anchor`](TnuaBuiltinClimb::desired_vec_to_anchor) and
+/// [`desired_forward`](TnuaBuiltinClimb::desired_forward) are ignored - instead, the character is
+/// aligned to the ladder's front face. The part of
+/// [`desired_climb_velocity`](TnuaBuiltinClimb::desired_climb_velocity) perpendicular to the up
+/// direction is used for dismounting to the side.
+///
+/// When the character dismounts, the action finishes. Use
+/// [`TnuaBlipReuseAvoidance`](crate::control_helpers::TnuaBlipReuseAvoidance) to prevent it from
+/// immediately mounting the ladder again.
+#[derive(Clone)]
+pub struct TnuaBuiltinClimbLadder {
+    /// The normal of the ladder's front face, pointing toward the character.
+    ///
+    /// Only the part of the normal perpendicular to the up direction is used.
+    pub normal: Dir3,
+
+    /// The distance to keep between the character's center and the
+    /// [`anchor`](TnuaBuiltinClimb::anchor), which should be on the ladder's front face.
+    pub distance_from_face: Float,
+
+    /// The distance between the rungs of the ladder.
+    ///
+    /// If set, the character will keep climbing to the nearest rung when there is no vertical
+    /// climb input, so that it always stops at a rung.
+    pub rung_spacing: Option<Float>,
+
+    /// A point at the height the center of the character should be at when it stands on one of
+    /// the rungs.
+    pub rung_origin: Vector3,
+
+    /// The maximum speed for reaching the nearest rung.
+    pub rung_snap_speed: Float,
+
+    /// The position the character moves to when it dismounts at the top of the ladder.
+    ///
+    /// This should be on the floor above the ladder - a bit in front of the top of the ladder (in
+    /// the direction opposite to the [`normal`](Self::normal)) and above it by the float height of
+    /// the character. If not set, the character will not dismount at the top.
+    ///
+    /// This is a ladder specific alternative to [`top_surface`](TnuaBuiltinClimb::top_surface),
+    /// for when the exact dismount position is known.
+    pub top_dismount_target: Option<Vector3>,
+
+    /// The character dismounts at the top when it climbs up and gets this close, vertically, to
+    /// the height of the [`top_dismount_target`](Self::top_dismount_target).
+    pub top_dismount_trigger_height: Float,
+
+    /// The speed of the movement to the [`top_dismount_target`](Self::top_dismount_target).
+    pub top_dismount_speed: Float,
+
+    /// Finish the action when the character climbs down and reaches the ground.
+    pub bottom_dismount: bool,
+
+    /// When the sideways input (the part of the
+    /// [`desired_climb_velocity`](TnuaBuiltinClimb::desired_climb_velocity) along the ladder's
+    /// face) is above this speed, the character jumps off the ladder to the side.
+    ///
+    /// If not set, the character will not dismount to the side.
+    pub side_dismount_threshold: Option<Float>,
+
+    /// The horizontal speed of the side dismount.
+    pub side_dismount_speed: Float,
+}
+
+impl Default for TnuaBuiltinClimbLadder {
+    fn default() -> Self {
+        Self {
+            normal: Dir3::Z,
+            distance_from_face: 0.5,
+            rung_spacing: None,
+            rung_origin: Vector3::ZERO,
+            rung_snap_speed: 2.0,
+            top_dismount_target: None,
+            top_dismount_trigger_height: 0.5,
+            top_dismount_speed: 3.0,
+            bottom_dismount: true,
+            side_dismount_threshold: None,
+            side_dismount_speed: 3.0,
+        }
+    }
+}
+
+/// Pole specific behavior for [`TnuaBuiltinClimb`].
+///
+/// When climbing a pole, the [`anchor`](TnuaBuiltinClimb::anchor) should be a point on the pole's
+/// central axis (e.g. the translation of the pole entity) and the character will keep at
+/// [`radius`](Self::radius) from it, facing it.
+/// [`desired_vec_to_anchor`](TnuaBuiltinClimb::desired_vec_to_anchor) and
+/// [`desired_forward`](TnuaBuiltinClimb::desired_forward) are ignored, and the part of
+/// [`desired_climb_velocity`](TnuaBuiltinClimb::desired_climb_velocity) perpendicular to the up
+/// direction is used for spinning around the pole.
+#[derive(Clone)]
+pub struct TnuaBuiltinClimbPole {
+    /// The distance to keep between the character's center and the pole's central axis.
+    pub radius: Float,
+
+    /// The maximum speed, along the circle around the pole, of the spinning.
+    pub max_spin_speed: Float,
+}
+
+impl Default for TnuaBuiltinClimbPole {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            max_spin_speed: 3.0,
+        }
+    }
 }
 
 impl Default for TnuaBuiltinClimb {
@@ -79,7 +232,11 @@ impl Default for TnuaBuiltinClimb {
             desired_forward: None,
             hard_stop_up: None,
             hard_stop_down: None,
+            top_surface: None,
+            top_out_reach: 1.0,
+            top_out_speed: 3.0,
             initiation_direction: Vector3::ZERO,
+            variant: Default::default(),
         }
     }
 }
@@ -118,15 +275,127 @@ impl TnuaAction for TnuaBuiltinClimb {
                         .velocity
                         .project_onto(ctx.up_direction.adjust_precision());
 
-                    motor
-                        .lin
-                        .cancel_on_axis(ctx.up_direction.adjust_precision());
+                    let up = ctx.up_direction.adjust_precision();
+
+                    if let Some(top_surface) = self.top_surface {
+                        if 0.0 < self.desired_climb_velocity.dot(up)
+                            && (top_surface - ctx.tracker.translation).dot(up) <= self.top_out_reach
+                        {
+                            let float_height = ctx
+                                .concrete_basis::<TnuaBuiltinWalk>()
+                                .map_or(0.0, |(walk, _)| walk.float_height);
+                            *state = TnuaBuiltinClimbState::ToppingOut {
+                                start: ctx.tracker.translation,
+                                target: top_surface + float_height * up,
+                                progress: 0.0,
+                            };
+                            continue;
+                        }
+                    }
+
+                    let mut desired_vec_to_anchor = self.desired_vec_to_anchor;
+                    let mut desired_forward = self.desired_forward;
+                    let mut desired_climb_speed = self.desired_climb_velocity.dot(up);
+
+                    match &self.variant {
+                        TnuaBuiltinClimbVariant::Free => {}
+                        TnuaBuiltinClimbVariant::Ladder(ladder) => {
+                            let normal = ladder
+                                .normal
+                                .adjust_precision()
+                                .reject_from(up)
+                                .normalize_or_zero();
+                            desired_vec_to_anchor = -ladder.distance_from_face * normal;
+                            desired_forward = Dir3::new(-normal.f32()).ok();
+
+                            let height = ctx.tracker.translation.dot(up);
+                            if let Some(target) = ladder.top_dismount_target {
+                                if 0.0 < desired_climb_speed
+                                    && target.dot(up) - ladder.top_dismount_trigger_height <= height
+                                {
+                                    *state = TnuaBuiltinClimbState::DismountingTop { target };
+                                    continue;
+                                }
+                            }
+                            if ladder.bottom_dismount
+                                && desired_climb_speed < 0.0
+                                && ctx
+                                    .basis
+                                    .displacement()
+                                    .is_some_and(|displacement| displacement.dot(up) <= 0.0)
+                            {
+                                return TnuaActionLifecycleDirective::Finished;
+                            }
+                            if let Some(threshold) = ladder.side_dismount_threshold {
+                                let sideways = normal.cross(up);
+                                let sideways_input = self.desired_climb_velocity.dot(sideways);
+                                if threshold < sideways_input.abs() {
+                                    motor.lin = Default::default();
+                                    motor.lin += ctx.adjust_horizontal_velocity(
+                                        ladder.side_dismount_speed
+                                            * sideways_input.signum()
+                                            * sideways,
+                                        Float::INFINITY,
+                                    );
+                                    *state = TnuaBuiltinClimbState::Coyote(Timer::from_seconds(
+                                        self.coyote_time.f32(),
+                                        TimerMode::Once,
+                                    ));
+                                    return TnuaActionLifecycleDirective::StillActive;
+                                }
+                            }
+
+                            if let (Some(rung_spacing), 0.0) =
+                                (ladder.rung_spacing, desired_climb_speed)
+                            {
+                                if 0.0 < rung_spacing {
+                                    let height_above_rung_origin =
+                                        height - ladder.rung_origin.dot(up);
+                                    // Snap to the rung nearest to where the character would stop
+                                    let vertical_velocity = climbing_velocity.dot(up);
+                                    let braking_distance = vertical_velocity
+                                        * vertical_velocity.abs()
+                                        / (2.0 * self.climb_acceleration);
+                                    let rung = ((height_above_rung_origin + braking_distance)
+                                        / rung_spacing)
+                                        .round();
+                                    desired_climb_speed = ((rung * rung_spacing
+                                        - height_above_rung_origin)
+                                        / ctx.frame_duration)
+                                        .clamp(-ladder.rung_snap_speed, ladder.rung_snap_speed);
+                                }
+                            }
+                        }
+                        TnuaBuiltinClimbVariant::Pole(pole) => {
+                            let current_forward = ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z);
+                            let direction_from_axis = (ctx.tracker.translation - self.anchor)
+                                .reject_from(up)
+                                .try_normalize()
+                                .or_else(|| (-current_forward).reject_from(up).try_normalize())
+                                .unwrap_or(Vector3::ZERO);
+                            let tangent = up.cross(direction_from_axis);
+                            let spin_speed = self
+                                .desired_climb_velocity
+                                .dot(tangent)
+                                .clamp(-pole.max_spin_speed, pole.max_spin_speed);
+                            let direction_from_axis = if 0.0 < pole.radius {
+                                Quaternion::from_axis_angle(
+                                    up,
+                                    spin_speed * ctx.frame_duration / pole.radius,
+                                )
+                                .mul_vec3(direction_from_axis)
+                            } else {
+                                direction_from_axis
+                            };
+                            desired_vec_to_anchor = -pole.radius * direction_from_axis;
+                            desired_forward = Dir3::new(-direction_from_axis.f32()).ok();
+                        }
+                    }
+
+                    motor.lin.cancel_on_axis(up);
                     motor.lin += ctx.negate_gravity();
-                    motor.lin += ctx.adjust_vertical_velocity(
-                        self.desired_climb_velocity
-                            .dot(ctx.up_direction.adjust_precision()),
-                        self.climb_acceleration,
-                    );
+                    motor.lin +=
+                        ctx.adjust_vertical_velocity(desired_climb_speed, self.climb_acceleration);
 
                     if let Some(stop_at) = self.hard_stop_up {
                         motor.lin += ctx.hard_stop(ctx.up_direction, stop_at, &motor.lin);
@@ -137,7 +406,7 @@ impl TnuaAction for TnuaBuiltinClimb {
 
                     let vec_to_anchor = (self.anchor - ctx.tracker.translation)
                         .reject_from(ctx.up_direction().adjust_precision());
-                    let horizontal_displacement = self.desired_vec_to_anchor - vec_to_anchor;
+                    let horizontal_displacement = desired_vec_to_anchor - vec_to_anchor;
 
                     let desired_horizontal_velocity = -horizontal_displacement / ctx.frame_duration;
 
@@ -146,7 +415,7 @@ impl TnuaAction for TnuaBuiltinClimb {
                         self.anchor_acceleration,
                     );
 
-                    if let Some(desired_forward) = self.desired_forward {
+                    if let Some(desired_forward) = desired_forward {
                         motor
                             .ang
                             .cancel_on_axis(ctx.up_direction.adjust_precision());
@@ -155,6 +424,75 @@ impl TnuaAction for TnuaBuiltinClimb {
 
                     lifecycle_status.directive_simple()
                 }
+                TnuaBuiltinClimbState::DismountingTop { target } => {
+                    if matches!(lifecycle_status, TnuaActionLifecycleStatus::CancelledInto) {
+                        return TnuaActionLifecycleDirective::Finished;
+                    }
+                    let TnuaBuiltinClimbVariant::Ladder(ladder) = &self.variant else {
+                        return TnuaActionLifecycleDirective::Finished;
+                    };
+                    let up = ctx.up_direction.adjust_precision();
+                    let displacement = *target - ctx.tracker.translation;
+                    let horizontal_displacement = displacement.reject_from(up);
+                    if ctx.basis.displacement().is_some()
+                        && horizontal_displacement.length() < 0.5 * ladder.distance_from_face
+                    {
+                        return TnuaActionLifecycleDirective::Finished;
+                    }
+
+                    // Only step forward after rising above the floor
+                    let risen_above_top = displacement.dot(up) <= TOP_HEIGHT_TOLERANCE;
+                    self.move_over_top(
+                        &ctx,
+                        motor,
+                        displacement,
+                        ladder.top_dismount_speed,
+                        risen_above_top,
+                    );
+
+                    TnuaActionLifecycleDirective::StillActive
+                }
+                TnuaBuiltinClimbState::ToppingOut {
+                    start,
+                    target,
+                    progress,
+                } => {
+                    if matches!(lifecycle_status, TnuaActionLifecycleStatus::CancelledInto) {
+                        return TnuaActionLifecycleDirective::Finished;
+                    }
+                    let up = ctx.up_direction.adjust_precision();
+                    let displacement = *target - ctx.tracker.translation;
+                    let vertical_displacement = displacement.dot(up);
+                    let horizontal_displacement = displacement.reject_from(up);
+
+                    let total_distance = (*target - *start).dot(up).abs()
+                        + (*target - *start).reject_from(up).length();
+                    let distance_left =
+                        vertical_displacement.abs() + horizontal_displacement.length();
+                    *progress = if 0.0 < total_distance {
+                        (1.0 - distance_left / total_distance).clamp(0.0, 1.0)
+                    } else {
+                        1.0
+                    };
+
+                    let frame_distance = self.top_out_speed * ctx.frame_duration;
+                    // Only move over the top after rising above it
+                    let risen_above_top =
+                        vertical_displacement <= frame_distance + TOP_HEIGHT_TOLERANCE;
+                    if risen_above_top && horizontal_displacement.length() <= frame_distance {
+                        return TnuaActionLifecycleDirective::Finished;
+                    }
+
+                    self.move_over_top(
+                        &ctx,
+                        motor,
+                        displacement,
+                        self.top_out_speed,
+                        risen_above_top,
+                    );
+
+                    TnuaActionLifecycleDirective::StillActive
+                }
                 TnuaBuiltinClimbState::Coyote(timer) => {
                     if timer.tick(ctx.frame_duration_as_duration()).finished() {
                         TnuaActionLifecycleDirective::Finished
@@ -181,9 +519,52 @@ impl TnuaAction for TnuaBuiltinClimb {
     }
 }
 
+impl TnuaBuiltinClimb {
+    /// Rise to the height of the `displacement`, and only move horizontally once the character
+    /// has `risen_above_top`.
+    fn move_over_top(
+        &self,
+        ctx: &TnuaActionContext,
+        motor: &mut TnuaMotor,
+        displacement: Vector3,
+        speed: Float,
+        risen_above_top: bool,
+    ) {
+        let up = ctx.up_direction.adjust_precision();
+        motor.lin.cancel_on_axis(up);
+        motor.lin += ctx.negate_gravity();
+        motor.lin += ctx.adjust_vertical_velocity(
+            (displacement.dot(up) / ctx.frame_duration).clamp(-speed, speed),
+            self.climb_acceleration,
+        );
+        let desired_horizontal_velocity = if risen_above_top {
+            (displacement.reject_from(up) / ctx.frame_duration).clamp_length_max(speed)
+        } else {
+            Vector3::ZERO
+        };
+        motor.lin +=
+            ctx.adjust_horizontal_velocity(desired_horizontal_velocity, self.anchor_acceleration);
+    }
+}
+
 #[derive(Debug)]
 pub enum TnuaBuiltinClimbState {
-    Climbing { climbing_velocity: Vector3 },
+    Climbing {
+        climbing_velocity: Vector3,
+    },
+    /// Dismounting a [ladder](TnuaBuiltinClimbLadder) at its top, moving to the `target`.
+    DismountingTop {
+        target: Vector3,
+    },
+    /// Climbing over the [`top_surface`](TnuaBuiltinClimb::top_surface).
+    ToppingOut {
+        /// The position of the character when it started climbing over the top.
+        start: Vector3,
+        /// The position the character will stand at on the top.
+        target: Vector3,
+        /// How much of the way from `start` to `target` was covered, from `0.0` to `1.0`.
+        progress: Float,
+    },
     Coyote(Timer),
 }
 
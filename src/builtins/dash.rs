@@ -1,27 +1,34 @@
 use crate::math::{AdjustPrecision, AsF32, Float, Vector3};
 use bevy::prelude::*;
 
-use crate::util::MotionHelper;
+use crate::util::{interpolate_sorted_entries, MotionHelper};
 use crate::{
     prelude::*, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
     TnuaActionLifecycleStatus, TnuaMotor,
 };
 
 /// The basic dash [action](TnuaAction).
+///
+/// The dash can optionally start with a short [freeze](Self::freeze_duration), allow redirecting
+/// it [shortly after it starts](Self::redirect_window), [snap](Self::snap_directions) its direction
+/// and control how much of its speed is [carried out](Self::exit_speed_curve) of it - which can be
+/// used for Celeste-style air dashes.
 #[derive(Clone, Debug)]
 pub struct TnuaBuiltinDash {
     /// The direction and distance of the dash.
     ///
     /// This input parameter is cached when the action starts. This means that the control system
     /// does not have to make sure the direction reamins the same even if the player changes it
-    /// mid-dash.
+    /// mid-dash. The only exceptions are the [freeze](Self::freeze_duration) and the
+    /// [`redirect_window`](Self::redirect_window), during which changes to it will redirect the
+    /// dash.
     pub displacement: Vector3,
 
     /// Point the negative Z axis of the characetr model in that direction during the dash.
     ///
-    /// This input parameter is cached when the action starts. This means that the control system
-    /// does not have to make sure the direction reamins the same even if the player changes it
-    /// mid-dash.
+    /// This input parameter is cached when the action starts (and when the dash gets redirected).
+    /// This means that the control system does not have to make sure the direction reamins the
+    /// same even if the player changes it mid-dash.
     pub desired_forward: Option<Dir3>,
 
     /// Allow this action to start even if the character is not touching ground nor in coyote time.
@@ -45,6 +52,39 @@ pub struct TnuaBuiltinDash {
     /// possible (typically when a character is still in the air and about the land) and the dash
     /// action would still get registered and be executed once the dash is possible.
     pub input_buffer_time: Float,
+
+    /// A duration, in seconds, to freeze the character in place before the dash starts (also known
+    /// as "hit-stop").
+    ///
+    /// Gravity is cancelled during the freeze, and the direction of the dash is only decided when
+    /// it ends - so the player can still aim the dash during it.
+    pub freeze_duration: Float,
+
+    /// A duration, in seconds, after the dash starts during which the dash will follow changes to
+    /// the [`displacement`](Self::displacement).
+    ///
+    /// The distance of a redirected dash is measured from where the dash started.
+    pub redirect_window: Float,
+
+    /// If not empty, the direction of the dash will be snapped to the closest of these directions.
+    ///
+    /// For example, set it to the 8 directions of the plane the character moves in for an 8-way
+    /// dash. Leave it empty for analog direction control.
+    pub snap_directions: Vec<Dir3>,
+
+    /// Keep cancelling the gravity after the dash, while braking in the air.
+    ///
+    /// Gravity is always cancelled during the dash itself.
+    pub suspend_gravity_while_braking: bool,
+
+    /// The maximum speed, in the dash direction, the character can keep after the dash, based on
+    /// the time since the dash ended.
+    ///
+    /// Each entry is a time, in seconds, and the speed at that time. The entries must be sorted by
+    /// their times, and the speeds between them are interpolated linearly. The action ends at the
+    /// time of the last entry. When empty, [`brake_to_speed`](Self::brake_to_speed) is used
+    /// instead.
+    pub exit_speed_curve: Vec<(Float, Float)>,
 }
 
 impl Default for TnuaBuiltinDash {
@@ -58,7 +98,44 @@ impl Default for TnuaBuiltinDash {
             acceleration: 400.0,
             brake_acceleration: 200.0,
             input_buffer_time: 0.2,
+            freeze_duration: 0.0,
+            redirect_window: 0.0,
+            snap_directions: Vec::new(),
+            suspend_gravity_while_braking: false,
+            exit_speed_curve: Vec::new(),
+        }
+    }
+}
+
+impl TnuaBuiltinDash {
+    /// The [`displacement`](Self::displacement), snapped to the closest of the
+    /// [`snap_directions`](Self::snap_directions).
+    ///
+    /// Returns `None` if the displacement is zero or not finite.
+    pub fn snapped_displacement(&self) -> Option<Vector3> {
+        if !self.displacement.is_finite() || self.displacement == Vector3::ZERO {
+            return None;
         }
+        let Some(snap_direction) = self.snap_directions.iter().max_by(|a, b| {
+            let a = a.adjust_precision().dot(self.displacement);
+            let b = b.adjust_precision().dot(self.displacement);
+            a.total_cmp(&b)
+        }) else {
+            return Some(self.displacement);
+        };
+        Some(snap_direction.adjust_precision() * self.displacement.length())
+    }
+
+    fn start_dash(&self, origin: Vector3) -> Option<TnuaBuiltinDashState> {
+        let displacement = self.snapped_displacement()?;
+        Some(TnuaBuiltinDashState::During {
+            direction: Dir3::new(displacement.f32()).ok()?,
+            origin,
+            destination: origin + displacement,
+            desired_forward: self.desired_forward,
+            consider_blocked_if_speed_is_less_than: Float::NEG_INFINITY,
+            elapsed: 0.0,
+        })
     }
 }
 
@@ -72,7 +149,7 @@ impl TnuaAction for TnuaBuiltinDash {
         ctx: crate::TnuaActionContext,
         being_fed_for: &bevy::time::Stopwatch,
     ) -> crate::TnuaActionInitiationDirective {
-        if !self.displacement.is_finite() || self.displacement == Vector3::ZERO {
+        if self.snapped_displacement().is_none() {
             TnuaActionInitiationDirective::Reject
         } else if self.allow_in_air || !ctx.basis.is_airborne() {
             // Either not airborne, or air jumps are allowed
@@ -92,33 +169,65 @@ impl TnuaAction for TnuaBuiltinDash {
         motor: &mut TnuaMotor,
     ) -> TnuaActionLifecycleDirective {
         // TODO: Once `std::mem::variant_count` gets stabilized, use that instead.
-        for _ in 0..3 {
+        for _ in 0..4 {
             return match state {
                 TnuaBuiltinDashState::PreDash => {
-                    let Ok(direction) = Dir3::new(self.displacement.f32()) else {
+                    if 0.0 < self.freeze_duration {
+                        *state = TnuaBuiltinDashState::Freeze { elapsed: 0.0 };
+                    } else if let Some(during) = self.start_dash(ctx.tracker.translation) {
+                        *state = during;
+                    } else {
                         // Probably unneeded because of the `initiation_decision`, but still
                         return TnuaActionLifecycleDirective::Finished;
-                    };
-                    *state = TnuaBuiltinDashState::During {
-                        direction,
-                        destination: ctx.tracker.translation + self.displacement,
-                        desired_forward: self.desired_forward,
-                        consider_blocked_if_speed_is_less_than: Float::NEG_INFINITY,
-                    };
+                    }
                     continue;
                 }
+                TnuaBuiltinDashState::Freeze { elapsed } => {
+                    if self.freeze_duration <= *elapsed {
+                        let Some(during) = self.start_dash(ctx.tracker.translation) else {
+                            return TnuaActionLifecycleDirective::Finished;
+                        };
+                        *state = during;
+                        continue;
+                    }
+                    *elapsed += ctx.frame_duration;
+
+                    motor.lin = Default::default();
+                    motor.lin.acceleration = -ctx.tracker.gravity;
+                    motor.lin.boost = -ctx.tracker.velocity;
+
+                    TnuaActionLifecycleDirective::StillActive
+                }
                 TnuaBuiltinDashState::During {
                     direction,
+                    origin,
                     destination,
                     desired_forward,
                     consider_blocked_if_speed_is_less_than,
+                    elapsed,
                 } => {
+                    if *elapsed < self.redirect_window {
+                        if let Some(displacement) = self.snapped_displacement() {
+                            if let Ok(new_direction) = Dir3::new(displacement.f32()) {
+                                if new_direction != *direction {
+                                    *direction = new_direction;
+                                    *destination = *origin + displacement;
+                                    *desired_forward = self.desired_forward;
+                                    // The speed in the new direction starts from scratch
+                                    *consider_blocked_if_speed_is_less_than = Float::NEG_INFINITY;
+                                }
+                            }
+                        }
+                    }
+                    *elapsed += ctx.frame_duration;
+
                     let distance_to_destination = direction
                         .adjust_precision()
                         .dot(*destination - ctx.tracker.translation);
                     if distance_to_destination < 0.0 {
                         *state = TnuaBuiltinDashState::Braking {
                             direction: *direction,
+                            elapsed: 0.0,
                         };
                         continue;
                     }
@@ -151,15 +260,29 @@ impl TnuaAction for TnuaBuiltinDash {
 
                     TnuaActionLifecycleDirective::StillActive
                 }
-                TnuaBuiltinDashState::Braking { direction } => {
+                TnuaBuiltinDashState::Braking { direction, elapsed } => {
                     let remaining_speed = direction.adjust_precision().dot(ctx.tracker.velocity);
-                    if remaining_speed <= self.brake_to_speed {
-                        TnuaActionLifecycleDirective::Finished
+                    let brake_to_speed =
+                        interpolate_sorted_entries(&self.exit_speed_curve, *elapsed)
+                            .unwrap_or(self.brake_to_speed);
+                    let finished = if let Some((curve_end, _)) = self.exit_speed_curve.last() {
+                        *curve_end <= *elapsed
                     } else {
+                        remaining_speed <= brake_to_speed
+                    };
+                    if finished {
+                        return TnuaActionLifecycleDirective::Finished;
+                    }
+                    *elapsed += ctx.frame_duration;
+
+                    if self.suspend_gravity_while_braking && ctx.basis.is_airborne() {
+                        motor.lin.acceleration = -ctx.tracker.gravity;
+                    }
+                    if brake_to_speed < remaining_speed {
                         motor.lin.boost = -direction.adjust_precision()
-                            * (remaining_speed - self.brake_to_speed).min(self.brake_acceleration);
-                        TnuaActionLifecycleDirective::StillActive
+                            * (remaining_speed - brake_to_speed).min(self.brake_acceleration);
                     }
+                    TnuaActionLifecycleDirective::StillActive
                 }
             };
         }
@@ -172,13 +295,21 @@ impl TnuaAction for TnuaBuiltinDash {
 pub enum TnuaBuiltinDashState {
     #[default]
     PreDash,
+    /// Frozen in place before the dash, for [`freeze_duration`](TnuaBuiltinDash::freeze_duration).
+    Freeze { elapsed: Float },
     During {
         direction: Dir3,
+        /// Where the dash started.
+        origin: Vector3,
         destination: Vector3,
         desired_forward: Option<Dir3>,
         consider_blocked_if_speed_is_less_than: Float,
+        /// The time, in seconds, since the dash started.
+        elapsed: Float,
     },
     Braking {
         direction: Dir3,
+        /// The time, in seconds, since the dash ended and the braking started.
+        elapsed: Float,
     },
 }
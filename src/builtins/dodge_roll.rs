@@ -0,0 +1,295 @@
+use crate::math::{AdjustPrecision, AsF32, Float, Vector3};
+use bevy::prelude::*;
+
+use crate::util::MotionHelper;
+use crate::{
+    TnuaAction, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
+    TnuaActionLifecycleStatus, TnuaMotor, TnuaVelChange,
+};
+
+use super::TnuaBuiltinWalk;
+
+/// An [action](TnuaAction) for dodge rolling.
+///
+/// The roll moves the character like a [dash](super::TnuaBuiltinDash) while lowering it like a
+/// [crouch](super::TnuaBuiltinCrouch) (the lowering only works when [`TnuaBuiltinWalk`] is the
+/// [basis](crate::TnuaBasis)). It goes through three timed phases - see
+/// [`TnuaBuiltinDodgeRollPhase`] - and gameplay code can check the state's
+/// [`is_invulnerable`](TnuaBuiltinDodgeRollState::is_invulnerable) to implement invulnerability
+/// frames.
+///
+/// Once started, the roll cannot be stopped before the
+/// [recovery](TnuaBuiltinDodgeRollPhase::Recovery) phase, and it will run until the end of that
+/// phase unless another action is fed.
+///
+/// To also change the shape of the proximity sensor during the roll (e.g. to match the lowered
+/// collider), use [`TnuaSensorShapeSwap`](crate::control_helpers::TnuaSensorShapeSwap).
+#[derive(Clone, Debug)]
+pub struct TnuaBuiltinDodgeRoll {
+    /// The direction and distance of the roll.
+    ///
+    /// Only the part perpendicular to the up direction is used. This input parameter is cached
+    /// when the action starts.
+    pub displacement: Vector3,
+
+    /// Point the negative Z axis of the characetr model in that direction during the roll.
+    ///
+    /// This input parameter is cached when the action starts.
+    pub desired_forward: Option<Dir3>,
+
+    /// Allow this action to start even if the character is not touching ground nor in coyote time.
+    pub allow_in_air: bool,
+
+    /// The speed the character will move in during the roll.
+    pub speed: Float,
+
+    /// The maximum acceleration when starting the roll.
+    pub acceleration: Float,
+
+    /// After the character covers the [`displacement`](Self::displacement), and during the
+    /// [recovery](TnuaBuiltinDodgeRollPhase::Recovery), the character will brake until its speed
+    /// is below that number.
+    pub brake_to_speed: Float,
+
+    /// The maximum acceleration when braking.
+    pub brake_acceleration: Float,
+
+    /// Controls how low the character will float during the roll, compared to its regular float
+    /// offset while standing.
+    ///
+    /// This field should typically have a negative value.
+    pub float_offset: Float,
+
+    /// A duration, in seconds, that it should take for the character to change its floating height
+    /// to start or stop the roll.
+    pub height_change_impulse_for_duration: Float,
+
+    /// The maximum impulse to apply when lowering or raising the character.
+    pub height_change_impulse_limit: Float,
+
+    /// The duration, in seconds, of the [startup](TnuaBuiltinDodgeRollPhase::Startup) phase.
+    pub startup_duration: Float,
+
+    /// The duration, in seconds, of the [active](TnuaBuiltinDodgeRollPhase::Active) phase, where
+    /// the character is invulnerable.
+    pub active_duration: Float,
+
+    /// The duration, in seconds, of the [recovery](TnuaBuiltinDodgeRollPhase::Recovery) phase.
+    pub recovery_duration: Float,
+
+    /// A duration, in seconds, where a player can press a roll button before a roll becomes
+    /// possible (typically when a character is still in the air and about the land) and the roll
+    /// action would still get registered and be executed once the roll is possible.
+    pub input_buffer_time: Float,
+}
+
+impl Default for TnuaBuiltinDodgeRoll {
+    fn default() -> Self {
+        Self {
+            displacement: Vector3::ZERO,
+            desired_forward: None,
+            allow_in_air: false,
+            speed: 10.0,
+            acceleration: 200.0,
+            brake_to_speed: 2.0,
+            brake_acceleration: 60.0,
+            float_offset: 0.0,
+            height_change_impulse_for_duration: 0.02,
+            height_change_impulse_limit: 40.0,
+            startup_duration: 0.05,
+            active_duration: 0.3,
+            recovery_duration: 0.2,
+            input_buffer_time: 0.2,
+        }
+    }
+}
+
+impl TnuaBuiltinDodgeRoll {
+    fn planar_displacement(&self, up: Vector3) -> Vector3 {
+        self.displacement.reject_from(up)
+    }
+
+    /// The phase of the roll after it went on for the given duration, or `None` if it is over.
+    pub fn phase_at(&self, elapsed: Float) -> Option<TnuaBuiltinDodgeRollPhase> {
+        if elapsed < self.startup_duration {
+            Some(TnuaBuiltinDodgeRollPhase::Startup)
+        } else if elapsed < self.startup_duration + self.active_duration {
+            Some(TnuaBuiltinDodgeRollPhase::Active)
+        } else if elapsed < self.startup_duration + self.active_duration + self.recovery_duration {
+            Some(TnuaBuiltinDodgeRollPhase::Recovery)
+        } else {
+            None
+        }
+    }
+
+    fn impulse_boost(&self, spring_offset: Float) -> Float {
+        let velocity_to_get_to_new_float_height =
+            spring_offset / self.height_change_impulse_for_duration;
+        velocity_to_get_to_new_float_height.clamp(
+            -self.height_change_impulse_limit,
+            self.height_change_impulse_limit,
+        )
+    }
+}
+
+impl TnuaAction for TnuaBuiltinDodgeRoll {
+    const NAME: &'static str = "TnuaBuiltinDodgeRoll";
+    type State = TnuaBuiltinDodgeRollState;
+    const VIOLATES_COYOTE_TIME: bool = true;
+
+    fn initiation_decision(
+        &self,
+        ctx: TnuaActionContext,
+        being_fed_for: &bevy::time::Stopwatch,
+    ) -> TnuaActionInitiationDirective {
+        let displacement = self.planar_displacement(ctx.up_direction.adjust_precision());
+        if !displacement.is_finite() || displacement == Vector3::ZERO {
+            TnuaActionInitiationDirective::Reject
+        } else if self.allow_in_air || !ctx.basis.is_airborne() {
+            TnuaActionInitiationDirective::Allow
+        } else if (being_fed_for.elapsed().as_secs_f64() as Float) < self.input_buffer_time {
+            TnuaActionInitiationDirective::Delay
+        } else {
+            TnuaActionInitiationDirective::Reject
+        }
+    }
+
+    fn apply(
+        &self,
+        state: &mut Self::State,
+        ctx: TnuaActionContext,
+        lifecycle_status: TnuaActionLifecycleStatus,
+        motor: &mut TnuaMotor,
+    ) -> TnuaActionLifecycleDirective {
+        let up = ctx.up_direction.adjust_precision();
+
+        if let TnuaBuiltinDodgeRollState::PreRoll = state {
+            let displacement = self.planar_displacement(up);
+            let Ok(direction) = Dir3::new(displacement.f32()) else {
+                // Probably unneeded because of the `initiation_decision`, but still
+                return TnuaActionLifecycleDirective::Finished;
+            };
+            *state = TnuaBuiltinDodgeRollState::Rolling {
+                direction,
+                destination: ctx.tracker.translation + displacement,
+                desired_forward: self.desired_forward,
+                phase: TnuaBuiltinDodgeRollPhase::Startup,
+                elapsed: 0.0,
+            };
+        }
+
+        let TnuaBuiltinDodgeRollState::Rolling {
+            direction,
+            destination,
+            desired_forward,
+            phase,
+            elapsed,
+        } = state
+        else {
+            return TnuaActionLifecycleDirective::Finished;
+        };
+
+        let Some(current_phase) = self.phase_at(*elapsed) else {
+            return TnuaActionLifecycleDirective::Finished;
+        };
+        *phase = current_phase;
+        if current_phase == TnuaBuiltinDodgeRollPhase::Recovery
+            && matches!(lifecycle_status, TnuaActionLifecycleStatus::CancelledInto)
+        {
+            return TnuaActionLifecycleDirective::Finished;
+        }
+        *elapsed += ctx.frame_duration;
+
+        // Override the horizontal movement of the basis
+        motor.lin.boost = motor.lin.boost.project_onto(up);
+        motor.lin.acceleration = motor.lin.acceleration.project_onto(up);
+
+        let direction_vector = direction.adjust_precision();
+        let distance_to_destination = direction_vector.dot(*destination - ctx.tracker.translation);
+        if current_phase != TnuaBuiltinDodgeRollPhase::Recovery && 0.0 < distance_to_destination {
+            let speed = self.speed.min(distance_to_destination / ctx.frame_duration);
+            motor.lin +=
+                ctx.adjust_horizontal_velocity(direction_vector * speed, self.acceleration);
+        } else {
+            let remaining_speed = direction_vector
+                .dot(ctx.tracker.velocity)
+                .clamp(0.0, self.brake_to_speed);
+            motor.lin += ctx.adjust_horizontal_velocity(
+                direction_vector * remaining_speed,
+                self.brake_acceleration,
+            );
+        }
+
+        if let (Some((walk_basis, walk_state)), Some(sensor_output)) = (
+            ctx.concrete_basis::<TnuaBuiltinWalk>(),
+            &ctx.proximity_sensor.output,
+        ) {
+            let float_offset = if current_phase == TnuaBuiltinDodgeRollPhase::Recovery {
+                0.0
+            } else {
+                self.float_offset
+            };
+            let spring_offset =
+                walk_basis.float_height - sensor_output.proximity.adjust_precision() + float_offset;
+            let spring_force =
+                walk_basis.spring_force(walk_state, &ctx.as_basis_context(), spring_offset);
+            let spring_force_boost = crate::util::calc_boost(&spring_force, ctx.frame_duration);
+            let impulse_boost = self.impulse_boost(spring_offset);
+            motor.lin.cancel_on_axis(up);
+            if spring_force_boost.length_squared() < impulse_boost.powi(2) {
+                motor.lin += TnuaVelChange::boost(impulse_boost * up);
+            } else {
+                motor.lin += spring_force;
+            }
+        }
+
+        if let Some(desired_forward) = desired_forward {
+            motor.ang.cancel_on_axis(up);
+            motor.ang += ctx.turn_to_direction(*desired_forward, ctx.up_direction);
+        }
+
+        TnuaActionLifecycleDirective::StillActive
+    }
+}
+
+/// The timing windows of a [`TnuaBuiltinDodgeRoll`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TnuaBuiltinDodgeRollPhase {
+    /// The character starts rolling, but is not invulnerable yet.
+    Startup,
+    /// The character rolls and is invulnerable.
+    Active,
+    /// The character slows down and gets back up. Other actions can cancel the roll during this
+    /// phase.
+    Recovery,
+}
+
+#[derive(Clone, Debug, Default)]
+pub enum TnuaBuiltinDodgeRollState {
+    #[default]
+    PreRoll,
+    Rolling {
+        direction: Dir3,
+        destination: Vector3,
+        desired_forward: Option<Dir3>,
+        phase: TnuaBuiltinDodgeRollPhase,
+        /// The time, in seconds, since the roll started.
+        elapsed: Float,
+    },
+}
+
+impl TnuaBuiltinDodgeRollState {
+    /// The current phase of the roll, or `None` if it did not start yet.
+    pub fn phase(&self) -> Option<TnuaBuiltinDodgeRollPhase> {
+        match self {
+            TnuaBuiltinDodgeRollState::PreRoll => None,
+            TnuaBuiltinDodgeRollState::Rolling { phase, .. } => Some(*phase),
+        }
+    }
+
+    /// Whether the character is in the [active](TnuaBuiltinDodgeRollPhase::Active) phase of the
+    /// roll, where it should be invulnerable.
+    pub fn is_invulnerable(&self) -> bool {
+        self.phase() == Some(TnuaBuiltinDodgeRollPhase::Active)
+    }
+}
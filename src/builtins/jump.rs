@@ -2,9 +2,10 @@ use crate::math::{AdjustPrecision, Float, Vector3};
 use bevy::prelude::*;
 use bevy::time::Stopwatch;
 
+use crate::builtins::TnuaBuiltinWalk;
 use crate::util::{
-    calc_angular_velchange_to_force_forward, SegmentedJumpDurationCalculator,
-    SegmentedJumpInitialVelocityCalculator, VelocityBoundary,
+    calc_angular_velchange_to_force_forward, interpolate_sorted_entries,
+    SegmentedJumpDurationCalculator, SegmentedJumpInitialVelocityCalculator, VelocityBoundary,
 };
 use crate::{
     TnuaAction, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
@@ -21,6 +22,10 @@ use crate::{
 /// being fed, it'll apply extra gravity to shorten the jump. If the game desires fixed height
 /// jumps instead (where the player cannot make lower jumps by tapping the jump button)
 /// [`shorten_extra_gravity`](Self::shorten_extra_gravity) should be set to `0.0`.
+///
+/// How much of the velocity of a moving platform the character keeps when jumping off it is
+/// controlled by the basis - see
+/// [`TnuaBuiltinWalk::launch_platform_velocity_inheritance`](crate::builtins::TnuaBuiltinWalk::launch_platform_velocity_inheritance).
 #[derive(Clone, Debug)]
 pub struct TnuaBuiltinJump {
     /// The height the character will jump to.
@@ -87,6 +92,49 @@ pub struct TnuaBuiltinJump {
     /// **NOTE**: This force will be added to the normal gravity.
     pub peak_prevention_extra_gravity: Float,
 
+    /// Used to let the character hang in the air for a bit at the peak of the jump.
+    ///
+    /// When the character's upward velocity (on the way up) or downward velocity (on the way down)
+    /// is below this value, [`apex_hang_reduced_gravity`](Self::apex_hang_reduced_gravity) will
+    /// be subtracted from the gravity. This takes precedence over
+    /// [`peak_prevention_extra_gravity`](Self::peak_prevention_extra_gravity) and
+    /// [`fall_extra_gravity`](Self::fall_extra_gravity).
+    ///
+    /// This reduced gravity is taken into account when calculating the initial jump speed, so the
+    /// character is still supposed to reach its full jump [`height`](Self::height).
+    ///
+    /// To disable, set this to `0.0`.
+    pub apex_hang_below_velocity: Float,
+
+    /// Reduced gravity for increasing the time the character spends at the peak of the jump.
+    ///
+    /// **NOTE**: This force will be subtracted from the normal gravity, and must be lower than it.
+    pub apex_hang_reduced_gravity: Float,
+
+    /// The minimal height the character will jump to, even if the jump action stops being fed
+    /// immediately.
+    ///
+    /// When the jump is shortened, [`shorten_extra_gravity`](Self::shorten_extra_gravity) will be
+    /// reduced as needed for the character to still reach this height.
+    pub min_height: Float,
+
+    /// The height the character will jump to, based on how long the jump action was fed.
+    ///
+    /// Each entry is a duration, in seconds, and the height the character will jump to if the
+    /// jump action stops being fed after being fed for that duration. The entries must be sorted
+    /// by their durations, and the heights between them are interpolated linearly. The height is
+    /// clamped between [`min_height`](Self::min_height) and [`height`](Self::height).
+    ///
+    /// When not empty, the extra gravity for shortening the jump will be calculated so that the
+    /// character reaches that height, instead of using
+    /// [`shorten_extra_gravity`](Self::shorten_extra_gravity).
+    pub height_curve: Vec<(Float, Float)>,
+
+    /// The maximum speed the character can fall at after reaching the top of the jump.
+    ///
+    /// To disable, set this to [`Float::INFINITY`].
+    pub max_fall_speed: Float,
+
     /// A duration, in seconds, after which the character would jump if the jump button was already
     /// pressed when the jump became available.
     ///
@@ -115,6 +163,13 @@ pub struct TnuaBuiltinJump {
     pub force_forward: Option<Dir3>,
 
     pub disable_force_forward_after_peak: bool,
+
+    /// How much of the upward velocity is reversed when the character hits a ceiling.
+    ///
+    /// When the jump [bonks a ceiling](TnuaBuiltinJumpState::bonk_ceiling) its upward velocity is
+    /// removed and the jump moves to the fall section. At `0.0` the character simply stops
+    /// rising, and higher values bounce it down from the ceiling.
+    pub ceiling_bonk_rebound: Float,
 }
 
 impl Default for TnuaBuiltinJump {
@@ -130,10 +185,16 @@ impl Default for TnuaBuiltinJump {
             shorten_extra_gravity: 60.0,
             peak_prevention_at_upward_velocity: 1.0,
             peak_prevention_extra_gravity: 20.0,
+            apex_hang_below_velocity: 0.0,
+            apex_hang_reduced_gravity: 0.0,
+            min_height: 0.0,
+            height_curve: Vec::new(),
+            max_fall_speed: Float::INFINITY,
             reschedule_cooldown: None,
             input_buffer_time: 0.2,
             force_forward: None,
             disable_force_forward_after_peak: true,
+            ceiling_bonk_rebound: 0.0,
         }
     }
 }
@@ -168,15 +229,9 @@ impl TnuaAction for TnuaBuiltinJump {
         let up = ctx.up_direction.adjust_precision();
 
         if lifecycle_status.just_started() {
-            let mut calculator = SegmentedJumpInitialVelocityCalculator::new(self.height);
             let gravity = ctx.tracker.gravity.dot(-up);
-            let kinetic_energy = calculator
-                .add_segment(
-                    gravity + self.peak_prevention_extra_gravity,
-                    self.peak_prevention_at_upward_velocity,
-                )
-                .add_segment(gravity, self.takeoff_above_velocity)
-                .add_final_segment(gravity + self.takeoff_extra_gravity)
+            let kinetic_energy = self
+                .initial_velocity_calculator(gravity)
                 .kinetic_energy()
                 .expect("`add_final_segment` should have covered remaining height");
             *state = TnuaBuiltinJumpState::StartingJump {
@@ -186,6 +241,12 @@ impl TnuaAction for TnuaBuiltinJump {
         }
 
         let effective_velocity = ctx.basis.effective_velocity();
+        let inherited_velocity = ctx
+            .concrete_basis::<TnuaBuiltinWalk>()
+            .map_or(Vector3::ZERO, |(_, walk_state)| {
+                walk_state.inherited_velocity
+            });
+        state.update_inherited_velocity(inherited_velocity);
 
         if let Some(force_forward) = self.force_forward {
             let disable_force_forward = self.disable_force_forward_after_peak
@@ -194,8 +255,8 @@ impl TnuaAction for TnuaBuiltinJump {
                     TnuaBuiltinJumpState::StartingJump { .. } => false,
                     TnuaBuiltinJumpState::SlowDownTooFastSlopeJump { .. } => false,
                     TnuaBuiltinJumpState::MaintainingJump { .. } => false,
-                    TnuaBuiltinJumpState::StoppedMaintainingJump => true,
-                    TnuaBuiltinJumpState::FallSection => true,
+                    TnuaBuiltinJumpState::StoppedMaintainingJump { .. } => true,
+                    TnuaBuiltinJumpState::FallSection { .. } => true,
                 };
             if !disable_force_forward {
                 motor
@@ -241,12 +302,26 @@ impl TnuaAction for TnuaBuiltinJump {
 
                     motor.lin.cancel_on_axis(up);
                     motor.lin.boost += (desired_upward_velocity - relative_velocity) * up;
+                    let zero_potential_energy_at = ctx.tracker.translation - extra_height * up;
                     if 0.0 <= extra_height {
                         *state = TnuaBuiltinJumpState::SlowDownTooFastSlopeJump {
                             origin: *origin,
                             desired_energy: *desired_energy,
-                            zero_potential_energy_at: ctx.tracker.translation - extra_height * up,
+                            zero_potential_energy_at,
+                        };
+                    }
+                    let target_height = self.height_for_duration(0.0);
+                    if matches!(lifecycle_status, TnuaActionLifecycleStatus::NoLongerFed)
+                        && 0.0 < target_height.unwrap_or(self.min_height)
+                    {
+                        // Released before the jump could properly start, but there is still a
+                        // minimal height to reach.
+                        *state = TnuaBuiltinJumpState::StoppedMaintainingJump {
+                            inherited_velocity,
+                            zero_potential_energy_at,
+                            target_height,
                         };
+                        return TnuaActionLifecycleDirective::StillActive;
                     }
                     self.directive_simple_or_reschedule(lifecycle_status)
                 }
@@ -257,10 +332,17 @@ impl TnuaAction for TnuaBuiltinJump {
                 } => {
                     let upward_velocity = up.dot(effective_velocity);
                     if upward_velocity <= ctx.basis.vertical_velocity() {
-                        *state = TnuaBuiltinJumpState::FallSection;
+                        *state = TnuaBuiltinJumpState::FallSection {
+                            inherited_velocity,
+                            bonked_ceiling: false,
+                        };
                         continue;
                     } else if !lifecycle_status.is_active() {
-                        *state = TnuaBuiltinJumpState::StoppedMaintainingJump;
+                        *state = TnuaBuiltinJumpState::StoppedMaintainingJump {
+                            inherited_velocity,
+                            zero_potential_energy_at: *zero_potential_energy_at,
+                            target_height: self.height_for_duration(0.0),
+                        };
                         continue;
                     }
                     let relative_velocity = effective_velocity.dot(up);
@@ -280,15 +362,7 @@ impl TnuaAction for TnuaBuiltinJump {
                                 .reject_from(ctx.up_direction.adjust_precision());
                             let already_moved = (ctx.tracker.translation - *origin)
                                 .project_onto(vertical_displacement.normalize_or_zero());
-                            let duration_to_top =
-                                SegmentedJumpDurationCalculator::new(relative_velocity)
-                                    .add_segment(
-                                        gravity + self.takeoff_extra_gravity,
-                                        self.takeoff_above_velocity,
-                                    )
-                                    .add_segment(gravity, self.peak_prevention_at_upward_velocity)
-                                    .add_segment(gravity + self.peak_prevention_extra_gravity, 0.0)
-                                    .duration();
+                            let duration_to_top = self.duration_to_top(gravity, relative_velocity);
                             let desired_vertical_velocity =
                                 (vertical_displacement - already_moved) / duration_to_top;
                             let desired_boost = (desired_vertical_velocity - effective_velocity)
@@ -301,8 +375,11 @@ impl TnuaAction for TnuaBuiltinJump {
                             );
                         }
                         *state = TnuaBuiltinJumpState::MaintainingJump {
+                            inherited_velocity,
                             wait_one_frame_before_updating_velocity_boundary: true,
                             velocity_boundary,
+                            zero_potential_energy_at: *zero_potential_energy_at,
+                            maintained_for: 0.0,
                         };
                         continue;
                     } else {
@@ -318,7 +395,13 @@ impl TnuaAction for TnuaBuiltinJump {
                 TnuaBuiltinJumpState::MaintainingJump {
                     wait_one_frame_before_updating_velocity_boundary,
                     velocity_boundary,
+                    zero_potential_energy_at,
+                    maintained_for,
+                    inherited_velocity: _,
                 } => {
+                    *maintained_for += ctx.frame_duration;
+                    let zero_potential_energy_at = *zero_potential_energy_at;
+                    let maintained_for = *maintained_for;
                     if let Some(velocity_boundary) = velocity_boundary {
                         if *wait_one_frame_before_updating_velocity_boundary {
                             *wait_one_frame_before_updating_velocity_boundary = false;
@@ -347,13 +430,20 @@ impl TnuaAction for TnuaBuiltinJump {
 
                     let relevant_upward_velocity = effective_velocity.dot(up);
                     if relevant_upward_velocity <= 0.0 {
-                        *state = TnuaBuiltinJumpState::FallSection;
+                        *state = TnuaBuiltinJumpState::FallSection {
+                            inherited_velocity,
+                            bonked_ceiling: false,
+                        };
                         motor.lin.cancel_on_axis(up);
                     } else {
                         motor.lin.cancel_on_axis(up);
-                        if relevant_upward_velocity < self.peak_prevention_at_upward_velocity {
+                        let [apex_hang_below, peak_prevention_below, takeoff_above] =
+                            self.rising_velocity_thresholds();
+                        if relevant_upward_velocity < apex_hang_below {
+                            motor.lin.acceleration += self.apex_hang_reduced_gravity * up;
+                        } else if relevant_upward_velocity < peak_prevention_below {
                             motor.lin.acceleration -= self.peak_prevention_extra_gravity * up;
-                        } else if self.takeoff_above_velocity <= relevant_upward_velocity {
+                        } else if takeoff_above <= relevant_upward_velocity {
                             motor.lin.acceleration -= self.takeoff_extra_gravity * up;
                         }
                     }
@@ -365,32 +455,65 @@ impl TnuaAction for TnuaBuiltinJump {
                         }
                         TnuaActionLifecycleStatus::CancelledInto => self.finish_or_reschedule(),
                         TnuaActionLifecycleStatus::NoLongerFed => {
-                            *state = TnuaBuiltinJumpState::StoppedMaintainingJump;
+                            *state = TnuaBuiltinJumpState::StoppedMaintainingJump {
+                                inherited_velocity,
+                                zero_potential_energy_at,
+                                target_height: self.height_for_duration(maintained_for),
+                            };
                             TnuaActionLifecycleDirective::StillActive
                         }
                     }
                 }
-                TnuaBuiltinJumpState::StoppedMaintainingJump => {
+                TnuaBuiltinJumpState::StoppedMaintainingJump {
+                    zero_potential_energy_at,
+                    target_height,
+                    inherited_velocity: _,
+                } => {
                     if matches!(lifecycle_status, TnuaActionLifecycleStatus::CancelledInto) {
                         self.finish_or_reschedule()
                     } else {
+                        let upward_velocity = up.dot(effective_velocity);
+                        let current_height =
+                            (ctx.tracker.translation - *zero_potential_energy_at).dot(up);
+                        let guaranteed_height = target_height.unwrap_or(self.min_height);
                         let landed = ctx
                             .basis
                             .displacement()
-                            .is_some_and(|displacement| displacement.dot(up) <= 0.0);
+                            .is_some_and(|displacement| displacement.dot(up) <= 0.0)
+                            && !(0.0 < upward_velocity && current_height < guaranteed_height);
                         if landed {
                             self.finish_or_reschedule()
                         } else {
-                            let upward_velocity = up.dot(effective_velocity);
                             if upward_velocity <= 0.0 {
-                                *state = TnuaBuiltinJumpState::FallSection;
+                                *state = TnuaBuiltinJumpState::FallSection {
+                                    inherited_velocity,
+                                    bonked_ceiling: false,
+                                };
                                 continue;
                             }
 
-                            let extra_gravity = if self.takeoff_above_velocity <= upward_velocity {
-                                self.shorten_extra_gravity + self.takeoff_extra_gravity
+                            let shorten_extra_gravity =
+                                if self.takeoff_above_velocity <= upward_velocity {
+                                    self.shorten_extra_gravity + self.takeoff_extra_gravity
+                                } else {
+                                    self.shorten_extra_gravity
+                                };
+                            let gravity = ctx.tracker.gravity.dot(-up);
+                            let extra_gravity_to_reach = |height: Float| {
+                                let remaining_height = height - current_height;
+                                (0.0 < remaining_height).then(|| {
+                                    (0.5 * upward_velocity.powi(2) / remaining_height - gravity)
+                                        .max(0.0)
+                                })
+                            };
+                            let extra_gravity = if let Some(target_height) = target_height {
+                                extra_gravity_to_reach(*target_height)
+                                    .unwrap_or(shorten_extra_gravity)
                             } else {
-                                self.shorten_extra_gravity
+                                extra_gravity_to_reach(self.min_height)
+                                    .map_or(shorten_extra_gravity, |extra_gravity| {
+                                        extra_gravity.min(shorten_extra_gravity)
+                                    })
                             };
 
                             motor.lin.cancel_on_axis(up);
@@ -399,7 +522,10 @@ impl TnuaAction for TnuaBuiltinJump {
                         }
                     }
                 }
-                TnuaBuiltinJumpState::FallSection => {
+                TnuaBuiltinJumpState::FallSection {
+                    bonked_ceiling,
+                    inherited_velocity: _,
+                } => {
                     let landed = ctx
                         .basis
                         .displacement()
@@ -410,7 +536,49 @@ impl TnuaAction for TnuaBuiltinJump {
                         self.finish_or_reschedule()
                     } else {
                         motor.lin.cancel_on_axis(up);
-                        motor.lin.acceleration -= self.fall_extra_gravity * up;
+                        let upward_velocity = up.dot(effective_velocity);
+                        // The character may still be rising here (e.g. after
+                        // `SlowDownTooFastSlopeJump`), so only hang once it starts falling.
+                        let fall_speed = -upward_velocity;
+                        if !*bonked_ceiling
+                            && 0.0 <= fall_speed
+                            && fall_speed < self.apex_hang_below_velocity
+                        {
+                            motor.lin.acceleration += self.apex_hang_reduced_gravity * up;
+                        } else {
+                            motor.lin.acceleration -= self.fall_extra_gravity * up;
+                        }
+                        if *bonked_ceiling && 0.0 < upward_velocity {
+                            motor.lin.boost -=
+                                (1.0 + self.ceiling_bonk_rebound) * upward_velocity * up;
+                        } else {
+                            let gravity = ctx.tracker.gravity.dot(-up);
+                            let next_upward_velocity = upward_velocity
+                                + (motor.lin.acceleration.dot(up) - gravity) * ctx.frame_duration;
+                            if next_upward_velocity < -self.max_fall_speed {
+                                motor.lin.cancel_on_axis(up);
+                                motor.lin.acceleration += gravity * up;
+                                motor.lin.boost += (-self.max_fall_speed - upward_velocity) * up;
+                            }
+                        }
+                        if let (Some((walk_basis, walk_state)), Some(sensor_output)) = (
+                            ctx.concrete_basis::<TnuaBuiltinWalk>(),
+                            &ctx.proximity_sensor.output,
+                        ) {
+                            if walk_basis.landing_prediction {
+                                let extra_upward_acceleration = motor.lin.acceleration.dot(up)
+                                    + motor.lin.boost.dot(up) / ctx.frame_duration;
+                                if let Some(braking_boost) = walk_basis.landing_braking_boost(
+                                    walk_state,
+                                    &ctx.as_basis_context(),
+                                    sensor_output.proximity.adjust_precision(),
+                                    extra_upward_acceleration,
+                                ) {
+                                    motor.lin.cancel_on_axis(up);
+                                    motor.lin.boost += braking_boost * up;
+                                }
+                            }
+                        }
                         TnuaActionLifecycleDirective::StillActive
                     }
                 }
@@ -432,6 +600,62 @@ impl TnuaBuiltinJump {
         }
     }
 
+    /// The upward velocities where the gravity changes while rising - apex hang, peak prevention
+    /// and takeoff.
+    fn rising_velocity_thresholds(&self) -> [Float; 3] {
+        let apex_hang_below = self.apex_hang_below_velocity.max(0.0);
+        let peak_prevention_below = self.peak_prevention_at_upward_velocity.max(apex_hang_below);
+        let takeoff_above = self.takeoff_above_velocity.max(peak_prevention_below);
+        [apex_hang_below, peak_prevention_below, takeoff_above]
+    }
+
+    fn initial_velocity_calculator(
+        &self,
+        gravity: Float,
+    ) -> SegmentedJumpInitialVelocityCalculator {
+        let [apex_hang_below, peak_prevention_below, takeoff_above] =
+            self.rising_velocity_thresholds();
+        let mut calculator = SegmentedJumpInitialVelocityCalculator::new(self.height);
+        calculator
+            .add_segment(gravity - self.apex_hang_reduced_gravity, apex_hang_below)
+            .add_segment(
+                gravity + self.peak_prevention_extra_gravity,
+                peak_prevention_below,
+            )
+            .add_segment(gravity, takeoff_above)
+            .add_final_segment(gravity + self.takeoff_extra_gravity);
+        calculator
+    }
+
+    /// The segments of the jump from the takeoff to the peak, as pairs of the gravity applied in
+    /// each segment and the upward velocity at its end.
+    fn rising_segments(&self, gravity: Float) -> [(Float, Float); 4] {
+        let [apex_hang_below, peak_prevention_below, takeoff_above] =
+            self.rising_velocity_thresholds();
+        [
+            (gravity + self.takeoff_extra_gravity, takeoff_above),
+            (gravity, peak_prevention_below),
+            (
+                gravity + self.peak_prevention_extra_gravity,
+                apex_hang_below,
+            ),
+            (gravity - self.apex_hang_reduced_gravity, 0.0),
+        ]
+    }
+
+    fn duration_to_top(&self, gravity: Float, upward_velocity: Float) -> Float {
+        let mut calculator = SegmentedJumpDurationCalculator::new(upward_velocity);
+        for (segment_gravity, velocity_threshold) in self.rising_segments(gravity) {
+            calculator.add_segment(segment_gravity, velocity_threshold);
+        }
+        calculator.duration()
+    }
+
+    fn height_for_duration(&self, duration: Float) -> Option<Float> {
+        let height = interpolate_sorted_entries(&self.height_curve, duration)?;
+        Some(height.max(self.min_height).min(self.height))
+    }
+
     fn directive_simple_or_reschedule(
         &self,
         lifecycle_status: TnuaActionLifecycleStatus,
@@ -444,6 +668,276 @@ impl TnuaBuiltinJump {
     }
 }
 
+impl TnuaBuiltinJump {
+    /// Predict the trajectory of the jump, assuming it is held for its full
+    /// [`height`](Self::height).
+    ///
+    /// * `walk` - the basis used while jumping. Its
+    ///   [`desired_velocity`](TnuaBuiltinWalk::desired_velocity) and
+    ///   [`air_acceleration`](TnuaBuiltinWalk::air_acceleration) determine how the horizontal
+    ///   velocity changes during the jump.
+    /// * `velocity` - the character's velocity at takeoff. Only the horizontal part is used,
+    ///   because the jump sets the vertical velocity by itself.
+    /// * `gravity` - the gravity applied to the character, without the jump's extra gravity.
+    /// * `landing_height` - the height, relative to the takeoff position, where the character is
+    ///   expected to land. If the jump does not reach it, the trajectory ends at the apex.
+    /// * `sample_interval` - the duration, in seconds, between the
+    ///   [`samples`](TnuaBuiltinJumpTrajectory::samples) of the trajectory.
+    ///
+    /// The prediction does not account for
+    /// [`vertical_displacement`](Self::vertical_displacement), for collisions, or for jumping up
+    /// slopes. Since the jump is assumed to be held, [`min_height`](Self::min_height) and
+    /// [`height_curve`](Self::height_curve) are not used either.
+    pub fn predict_trajectory(
+        &self,
+        walk: &TnuaBuiltinWalk,
+        velocity: Vector3,
+        gravity: Vector3,
+        landing_height: Float,
+        sample_interval: Float,
+    ) -> TnuaBuiltinJumpTrajectory {
+        let up = (-gravity).normalize_or(Vector3::Y);
+        let gravity = gravity.length();
+
+        let initial_upward_velocity = self
+            .initial_velocity_calculator(gravity)
+            .required_initial_velocity()
+            .expect("`add_final_segment` should have covered remaining height");
+
+        let mut vertical_phases = Vec::new();
+        let mut time = 0.0;
+        let mut height = 0.0;
+        let mut upward_velocity = initial_upward_velocity;
+        for (segment_gravity, velocity_threshold) in self.rising_segments(gravity) {
+            let duration = SegmentedJumpDurationCalculator::new(upward_velocity)
+                .add_segment(segment_gravity, velocity_threshold)
+                .duration();
+            if duration <= 0.0 {
+                continue;
+            }
+            let phase = VerticalPhase {
+                start_time: time,
+                start_height: height,
+                start_velocity: upward_velocity,
+                gravity: segment_gravity,
+            };
+            height = phase.height_at(time + duration);
+            time += duration;
+            upward_velocity = velocity_threshold;
+            vertical_phases.push(phase);
+        }
+        let time_to_apex = time;
+        let apex_height = height;
+
+        let rising_phases_count = vertical_phases.len();
+        let mut fall_speed: Float = 0.0;
+        // Like in the `FallSection`, the apex hang only applies while falling.
+        for (segment_gravity, speed_threshold) in [
+            (
+                gravity - self.apex_hang_reduced_gravity,
+                self.apex_hang_below_velocity.max(0.0),
+            ),
+            (gravity + self.fall_extra_gravity, Float::INFINITY),
+        ] {
+            let speed_threshold = speed_threshold.min(self.max_fall_speed);
+            if speed_threshold <= fall_speed {
+                continue;
+            }
+            if segment_gravity <= 0.0 {
+                // The apex hang cancels the gravity completely, so the character keeps its
+                // current fall speed (which is zero, since this can only be the apex hang).
+                vertical_phases.push(VerticalPhase {
+                    start_time: time,
+                    start_height: height,
+                    start_velocity: -fall_speed,
+                    gravity: 0.0,
+                });
+                break;
+            }
+            let phase = VerticalPhase {
+                start_time: time,
+                start_height: height,
+                start_velocity: -fall_speed,
+                gravity: segment_gravity,
+            };
+            vertical_phases.push(phase);
+            if speed_threshold.is_infinite() {
+                break;
+            }
+            let duration = (speed_threshold - fall_speed) / segment_gravity;
+            height = vertical_phases.last().unwrap().height_at(time + duration);
+            time += duration;
+            fall_speed = speed_threshold;
+        }
+        if self.max_fall_speed <= fall_speed {
+            vertical_phases.push(VerticalPhase {
+                start_time: time,
+                start_height: height,
+                start_velocity: -self.max_fall_speed,
+                gravity: 0.0,
+            });
+        }
+        let airtime = if landing_height <= apex_height {
+            vertical_phases[rising_phases_count..]
+                .iter()
+                .enumerate()
+                .find_map(|(index, phase)| {
+                    let landing_time = phase.time_to_height(landing_height)?;
+                    let phase_end_time = vertical_phases
+                        .get(rising_phases_count + index + 1)
+                        .map_or(Float::INFINITY, |next_phase| next_phase.start_time);
+                    (landing_time <= phase_end_time).then_some(landing_time)
+                })
+        } else {
+            None
+        };
+
+        let planar_velocity = velocity.reject_from_normalized(up);
+        let (desired_planar_velocity, time_to_desired_velocity) = if 0.0 < walk.air_acceleration {
+            let desired_planar_velocity = walk.desired_velocity.reject_from_normalized(up);
+            let velocity_difference = desired_planar_velocity - planar_velocity;
+            (
+                desired_planar_velocity,
+                velocity_difference.length() / walk.air_acceleration,
+            )
+        } else {
+            // Without air acceleration, the horizontal velocity stays the takeoff velocity.
+            (planar_velocity, 0.0)
+        };
+
+        let mut trajectory = TnuaBuiltinJumpTrajectory {
+            initial_upward_velocity,
+            apex_height,
+            time_to_apex,
+            apex_offset: Vector3::ZERO,
+            airtime,
+            landing_offset: None,
+            samples: Vec::new(),
+            up,
+            vertical_phases,
+            planar_velocity,
+            desired_planar_velocity,
+            time_to_desired_velocity,
+        };
+        trajectory.apex_offset = trajectory.offset_at(time_to_apex);
+        trajectory.landing_offset = airtime.map(|airtime| trajectory.offset_at(airtime));
+
+        let end_time = airtime.unwrap_or(time_to_apex);
+        if 0.0 < sample_interval {
+            let mut sample_time = 0.0;
+            while sample_time < end_time {
+                trajectory.samples.push(TnuaBuiltinJumpTrajectorySample {
+                    time: sample_time,
+                    offset: trajectory.offset_at(sample_time),
+                });
+                sample_time += sample_interval;
+            }
+        }
+        trajectory.samples.push(TnuaBuiltinJumpTrajectorySample {
+            time: end_time,
+            offset: trajectory.offset_at(end_time),
+        });
+
+        trajectory
+    }
+}
+
+/// The predicted trajectory of a jump, calculated by
+/// [`TnuaBuiltinJump::predict_trajectory`].
+///
+/// All the offsets are relative to the position of the character at takeoff.
+#[derive(Debug, Clone)]
+pub struct TnuaBuiltinJumpTrajectory {
+    /// The upward velocity the jump starts with.
+    pub initial_upward_velocity: Float,
+    /// The height of the jump's peak, relative to the takeoff position.
+    pub apex_height: Float,
+    /// The time, in seconds, it takes to reach the jump's peak.
+    pub time_to_apex: Float,
+    /// The offset of the jump's peak.
+    pub apex_offset: Vector3,
+    /// The time, in seconds, from takeoff until landing at the landing height. `None` if the jump
+    /// does not reach that height.
+    pub airtime: Option<Float>,
+    /// The offset of the landing point. `None` if the jump does not reach the landing height.
+    pub landing_offset: Option<Vector3>,
+    /// The trajectory, sampled from the takeoff until the landing (or until the peak, if the jump
+    /// does not reach the landing height)
+    pub samples: Vec<TnuaBuiltinJumpTrajectorySample>,
+    up: Vector3,
+    vertical_phases: Vec<VerticalPhase>,
+    planar_velocity: Vector3,
+    desired_planar_velocity: Vector3,
+    time_to_desired_velocity: Float,
+}
+
+/// A point on a [`TnuaBuiltinJumpTrajectory`].
+#[derive(Debug, Clone, Copy)]
+pub struct TnuaBuiltinJumpTrajectorySample {
+    /// The time, in seconds, since the takeoff.
+    pub time: Float,
+    /// The offset from the takeoff position.
+    pub offset: Vector3,
+}
+
+impl TnuaBuiltinJumpTrajectory {
+    /// Calculate the offset from the takeoff position at any time of the jump.
+    ///
+    /// After the peak, the character is assumed to keep falling indefinitely.
+    pub fn offset_at(&self, time: Float) -> Vector3 {
+        let height = self
+            .vertical_phases
+            .iter()
+            .rev()
+            .find(|phase| phase.start_time <= time)
+            .map_or(0.0, |phase| phase.height_at(time));
+
+        let acceleration_time = time.min(self.time_to_desired_velocity);
+        let velocity_difference = self.desired_planar_velocity - self.planar_velocity;
+        let mut planar_offset = self.planar_velocity * acceleration_time;
+        if 0.0 < self.time_to_desired_velocity {
+            planar_offset += 0.5 * velocity_difference / self.time_to_desired_velocity
+                * acceleration_time.powi(2);
+        }
+        planar_offset += self.desired_planar_velocity * (time - acceleration_time);
+
+        planar_offset + height * self.up
+    }
+}
+
+#[derive(Debug, Clone)]
+struct VerticalPhase {
+    start_time: Float,
+    start_height: Float,
+    start_velocity: Float,
+    gravity: Float,
+}
+
+impl VerticalPhase {
+    fn height_at(&self, time: Float) -> Float {
+        let duration = time - self.start_time;
+        self.start_height + self.start_velocity * duration - 0.5 * self.gravity * duration.powi(2)
+    }
+
+    /// The time a falling phase reaches the given height, assuming the phase goes on forever.
+    fn time_to_height(&self, height: Float) -> Option<Float> {
+        let height_to_lose = self.start_height - height;
+        if height_to_lose < 0.0 {
+            return None;
+        }
+        let duration = if 0.0 < self.gravity {
+            (self.start_velocity
+                + (self.start_velocity.powi(2) + 2.0 * self.gravity * height_to_lose).sqrt())
+                / self.gravity
+        } else if self.start_velocity < 0.0 {
+            -height_to_lose / self.start_velocity
+        } else {
+            return None;
+        };
+        Some(self.start_time + duration)
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub enum TnuaBuiltinJumpState {
     #[default]
@@ -467,7 +961,107 @@ pub enum TnuaBuiltinJumpState {
     MaintainingJump {
         wait_one_frame_before_updating_velocity_boundary: bool,
         velocity_boundary: Option<VelocityBoundary>,
+        zero_potential_energy_at: Vector3,
+        /// The duration, in seconds, the jump was maintained for.
+        maintained_for: Float,
+        /// The velocity inherited from the platform the character jumped from (or walked off).
+        ///
+        /// See [`TnuaBuiltinWalkState::inherited_velocity`](super::TnuaBuiltinWalkState::inherited_velocity).
+        inherited_velocity: Vector3,
+    },
+    StoppedMaintainingJump {
+        zero_potential_energy_at: Vector3,
+        /// The height the jump should reach, based on
+        /// [`height_curve`](TnuaBuiltinJump::height_curve).
+        target_height: Option<Float>,
+        /// The velocity inherited from the platform the character jumped from (or walked off).
+        ///
+        /// See [`TnuaBuiltinWalkState::inherited_velocity`](super::TnuaBuiltinWalkState::inherited_velocity).
+        inherited_velocity: Vector3,
+    },
+    FallSection {
+        /// Whether the jump was cut short because the character hit a ceiling.
+        ///
+        /// Useful for playing a head-bump animation.
+        bonked_ceiling: bool,
+        /// The velocity inherited from the platform the character jumped from (or walked off).
+        ///
+        /// See [`TnuaBuiltinWalkState::inherited_velocity`](super::TnuaBuiltinWalkState::inherited_velocity).
+        inherited_velocity: Vector3,
     },
-    StoppedMaintainingJump,
-    FallSection,
+}
+
+impl TnuaBuiltinJumpState {
+    /// Stop the jump from rising because the character hit a ceiling.
+    ///
+    /// This moves a rising jump to the [`FallSection`](Self::FallSection), where its upward
+    /// velocity will be removed (see
+    /// [`ceiling_bonk_rebound`](TnuaBuiltinJump::ceiling_bonk_rebound)). It has no effect if the
+    /// jump is not rising.
+    ///
+    /// Tnua cannot detect ceilings by itself. This can be called with data from the
+    /// [`TnuaObstacleRadar`](crate::TnuaObstacleRadar) via
+    /// [`TnuaController::concrete_action_mut`](crate::prelude::TnuaController::concrete_action_mut),
+    /// or automatically by the [`TnuaCeilingSensor`](crate::control_helpers::TnuaCeilingSensor)
+    /// helper.
+    pub fn bonk_ceiling(&mut self) {
+        match self {
+            TnuaBuiltinJumpState::NoJump
+            | TnuaBuiltinJumpState::StartingJump { .. }
+            | TnuaBuiltinJumpState::FallSection { .. } => {}
+            TnuaBuiltinJumpState::SlowDownTooFastSlopeJump { .. } => {
+                *self = TnuaBuiltinJumpState::FallSection {
+                    bonked_ceiling: true,
+                    inherited_velocity: Vector3::ZERO,
+                };
+            }
+            TnuaBuiltinJumpState::MaintainingJump {
+                inherited_velocity, ..
+            }
+            | TnuaBuiltinJumpState::StoppedMaintainingJump {
+                inherited_velocity, ..
+            } => {
+                *self = TnuaBuiltinJumpState::FallSection {
+                    bonked_ceiling: true,
+                    inherited_velocity: *inherited_velocity,
+                };
+            }
+        }
+    }
+
+    /// The velocity inherited from the platform the character jumped from, if the jump is
+    /// already in the air.
+    pub fn inherited_velocity(&self) -> Option<Vector3> {
+        match self {
+            TnuaBuiltinJumpState::NoJump
+            | TnuaBuiltinJumpState::StartingJump { .. }
+            | TnuaBuiltinJumpState::SlowDownTooFastSlopeJump { .. } => None,
+            TnuaBuiltinJumpState::MaintainingJump {
+                inherited_velocity, ..
+            }
+            | TnuaBuiltinJumpState::StoppedMaintainingJump {
+                inherited_velocity, ..
+            }
+            | TnuaBuiltinJumpState::FallSection {
+                inherited_velocity, ..
+            } => Some(*inherited_velocity),
+        }
+    }
+
+    fn update_inherited_velocity(&mut self, new_inherited_velocity: Vector3) {
+        match self {
+            TnuaBuiltinJumpState::NoJump
+            | TnuaBuiltinJumpState::StartingJump { .. }
+            | TnuaBuiltinJumpState::SlowDownTooFastSlopeJump { .. } => {}
+            TnuaBuiltinJumpState::MaintainingJump {
+                inherited_velocity, ..
+            }
+            | TnuaBuiltinJumpState::StoppedMaintainingJump {
+                inherited_velocity, ..
+            }
+            | TnuaBuiltinJumpState::FallSection {
+                inherited_velocity, ..
+            } => *inherited_velocity = new_inherited_velocity,
+        }
+    }
 }
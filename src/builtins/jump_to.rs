@@ -0,0 +1,242 @@
+use crate::math::{AdjustPrecision, Float, Vector3};
+use bevy::prelude::*;
+use bevy::time::Stopwatch;
+
+use crate::util::{SegmentedJumpDurationCalculator, SegmentedJumpInitialVelocityCalculator};
+use crate::{
+    TnuaAction, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
+    TnuaActionLifecycleStatus, TnuaMotor,
+};
+
+/// An [action](TnuaAction) for jumping to an exact landing point.
+///
+/// Unlike [`TnuaBuiltinJump`](crate::builtins::TnuaBuiltinJump), which is designed for player
+/// controlled jumps, this action calculates the launch velocity required to land on the
+/// [`target`](Self::target) and overrides the air control of the basis for the duration of the
+/// flight. This makes it suitable for NPC platforming and for scripted jumps in cutscenes.
+///
+/// The arc of the jump is determined by [`arc`](Self::arc). The flight uses regular gravity only,
+/// without the extra gravity segments of the regular jump, so that the arc remains predictable.
+///
+/// The action does not need to be fed continuously - once started, it'll keep going until the
+/// character lands. If the target moves (e.g. when jumping onto a moving platform) the action
+/// should be fed with the updated [`target`](Self::target) every frame, and the flight will be
+/// corrected in mid-air within the limit of
+/// [`max_correction_acceleration`](Self::max_correction_acceleration).
+#[derive(Clone, Debug)]
+pub struct TnuaBuiltinJumpTo {
+    /// The position the center of the character should land at.
+    ///
+    /// Note that this is the position of the character's center - not of its feet - so it should
+    /// usually be the point on the ground plus the float height of the character.
+    pub target: Vector3,
+
+    /// If the target is an entity, it can be set here so that helpers like
+    /// [`TnuaBlipReuseAvoidance`](crate::control_helpers::TnuaBlipReuseAvoidance) will know
+    /// about it.
+    ///
+    /// This does not make the action track the entity by itself - the
+    /// [`target`](Self::target) still needs to be updated when the entity moves.
+    pub target_entity: Option<Entity>,
+
+    /// How the arc of the jump is shaped.
+    pub arc: TnuaBuiltinJumpToArc,
+
+    /// Allow this action to start even if the character is not touching ground nor in coyote time.
+    pub allow_in_air: bool,
+
+    /// A duration, in seconds, where the action can be fed before it becomes possible (typically
+    /// when a character is still in the air and about the land) and it would still get registered
+    /// and be executed once it is possible.
+    pub input_buffer_time: Float,
+
+    /// The maximum acceleration used for correcting the flight when the character drifts from
+    /// the trajectory or when the [`target`](Self::target) moves.
+    ///
+    /// Set to `0.0` to disable mid-air corrections.
+    pub max_correction_acceleration: Float,
+
+    /// Stop correcting the flight when less than this duration, in seconds, is left before the
+    /// expected landing.
+    ///
+    /// The closer the character gets to the target, the larger the velocity changes required to
+    /// correct its trajectory. This prevents wild corrections right before landing.
+    pub stop_correcting_before_landing: Float,
+}
+
+/// Determines the shape of the arc of [`TnuaBuiltinJumpTo`].
+#[derive(Clone, Copy, Debug)]
+pub enum TnuaBuiltinJumpToArc {
+    /// Reach the specified height above the higher of the takeoff position and the target.
+    ApexHeight(Float),
+    /// Land on the target after the specified duration, in seconds.
+    Airtime(Float),
+}
+
+impl Default for TnuaBuiltinJumpTo {
+    fn default() -> Self {
+        Self {
+            target: Vector3::ZERO,
+            target_entity: None,
+            arc: TnuaBuiltinJumpToArc::ApexHeight(1.0),
+            allow_in_air: false,
+            input_buffer_time: 0.2,
+            max_correction_acceleration: 60.0,
+            stop_correcting_before_landing: 0.1,
+        }
+    }
+}
+
+impl TnuaBuiltinJumpTo {
+    /// Calculate the velocity required for landing on the [`target`](Self::target) when jumping
+    /// from `origin`, together with the duration of the flight.
+    ///
+    /// Returns `None` if the target cannot be reached with the configured [`arc`](Self::arc) -
+    /// e.g. when the airtime is not positive, or when there is no gravity to bring the character
+    /// down from the apex.
+    pub fn launch_velocity(&self, origin: Vector3, gravity: Vector3) -> Option<(Vector3, Float)> {
+        let up = (-gravity).normalize_or(Vector3::Y);
+        let gravity = gravity.length();
+        let displacement = self.target - origin;
+        let target_height = displacement.dot(up);
+
+        let (upward_velocity, airtime) = match self.arc {
+            TnuaBuiltinJumpToArc::ApexHeight(apex_height) => {
+                if gravity <= 0.0 || apex_height < 0.0 {
+                    return None;
+                }
+                let jump_height = target_height.max(0.0) + apex_height;
+                let upward_velocity = SegmentedJumpInitialVelocityCalculator::new(jump_height)
+                    .add_final_segment(gravity)
+                    .required_initial_velocity()
+                    .ok()?;
+                let time_to_apex = SegmentedJumpDurationCalculator::new(upward_velocity)
+                    .add_segment(gravity, 0.0)
+                    .duration();
+                let time_from_apex = (2.0 * (jump_height - target_height) / gravity).sqrt();
+                (upward_velocity, time_to_apex + time_from_apex)
+            }
+            TnuaBuiltinJumpToArc::Airtime(airtime) => {
+                if airtime <= 0.0 {
+                    return None;
+                }
+                (
+                    Self::upward_velocity_for_duration(target_height, gravity, airtime),
+                    airtime,
+                )
+            }
+        };
+        if !airtime.is_finite() || airtime <= 0.0 {
+            return None;
+        }
+
+        let planar_velocity = displacement.reject_from_normalized(up) / airtime;
+        Some((planar_velocity + upward_velocity * up, airtime))
+    }
+
+    fn upward_velocity_for_duration(height: Float, gravity: Float, duration: Float) -> Float {
+        height / duration + 0.5 * gravity * duration
+    }
+}
+
+impl TnuaAction for TnuaBuiltinJumpTo {
+    const NAME: &'static str = "TnuaBuiltinJumpTo";
+    type State = TnuaBuiltinJumpToState;
+    const VIOLATES_COYOTE_TIME: bool = true;
+
+    fn initiation_decision(
+        &self,
+        ctx: TnuaActionContext,
+        being_fed_for: &Stopwatch,
+    ) -> TnuaActionInitiationDirective {
+        if !self.target.is_finite()
+            || self
+                .launch_velocity(ctx.tracker.translation, ctx.tracker.gravity)
+                .is_none()
+        {
+            TnuaActionInitiationDirective::Reject
+        } else if self.allow_in_air || !ctx.basis.is_airborne() {
+            TnuaActionInitiationDirective::Allow
+        } else if (being_fed_for.elapsed().as_secs_f64() as Float) < self.input_buffer_time {
+            TnuaActionInitiationDirective::Delay
+        } else {
+            TnuaActionInitiationDirective::Reject
+        }
+    }
+
+    fn apply(
+        &self,
+        state: &mut Self::State,
+        ctx: TnuaActionContext,
+        lifecycle_status: TnuaActionLifecycleStatus,
+        motor: &mut TnuaMotor,
+    ) -> TnuaActionLifecycleDirective {
+        if matches!(lifecycle_status, TnuaActionLifecycleStatus::CancelledInto) {
+            return TnuaActionLifecycleDirective::Finished;
+        }
+
+        let up = ctx.up_direction.adjust_precision();
+
+        match state {
+            TnuaBuiltinJumpToState::PreLaunch => {
+                let Some((velocity, airtime)) =
+                    self.launch_velocity(ctx.tracker.translation, ctx.tracker.gravity)
+                else {
+                    return TnuaActionLifecycleDirective::Finished;
+                };
+                motor.lin = Default::default();
+                motor.lin.boost = velocity - ctx.tracker.velocity;
+                *state = TnuaBuiltinJumpToState::Flight { time_left: airtime };
+                TnuaActionLifecycleDirective::StillActive
+            }
+            TnuaBuiltinJumpToState::Flight { time_left } => {
+                *time_left -= ctx.frame_duration;
+
+                let descending = ctx.tracker.velocity.dot(up) <= 0.0;
+                let landed = ctx
+                    .basis
+                    .displacement()
+                    .is_some_and(|displacement| displacement.dot(up) <= 0.0);
+                if descending && (landed || *time_left <= 0.0) {
+                    return TnuaActionLifecycleDirective::Finished;
+                }
+
+                // Override the air control of the basis
+                motor.lin = Default::default();
+
+                if 0.0 < self.max_correction_acceleration
+                    && self.stop_correcting_before_landing < *time_left
+                {
+                    let displacement = self.target - ctx.tracker.translation;
+                    let gravity = ctx.tracker.gravity.dot(-up);
+                    let desired_velocity = displacement.reject_from_normalized(up) / *time_left
+                        + Self::upward_velocity_for_duration(
+                            displacement.dot(up),
+                            gravity,
+                            *time_left,
+                        ) * up;
+                    motor.lin.boost = (desired_velocity - ctx.tracker.velocity)
+                        .clamp_length_max(ctx.frame_duration * self.max_correction_acceleration);
+                }
+
+                TnuaActionLifecycleDirective::StillActive
+            }
+        }
+    }
+
+    fn target_entity(&self, _state: &Self::State) -> Option<Entity> {
+        self.target_entity
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub enum TnuaBuiltinJumpToState {
+    /// The action has not launched the character yet.
+    #[default]
+    PreLaunch,
+    /// The character is flying toward the target.
+    Flight {
+        /// The time, in seconds, left until the character is expected to land.
+        time_left: Float,
+    },
+}
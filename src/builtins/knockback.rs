@@ -22,6 +22,15 @@ use bevy::prelude::*;
 /// * [`barrier_strength_diminishing`](Self::barrier_strength_diminishing). Setting it too low
 ///   makes it very hard for the character to push through the boundary. It starts getting slightly
 ///   weird below 1.0, and really weird below 0.5. Better keep it at above - 1.0 levels.
+///
+/// For fighting-game style knockback, the action can also:
+/// * Ignore the character's input for a [hit-stun](Self::hit_stun_duration) duration.
+/// * [Scale](Self::launch_multiplier) the shove (e.g. by damage or weight) and
+///   [aim](Self::launch_angle) it at a fixed launch angle.
+/// * Keep a character the shove launched upward in the air [until it lands](Self::until_landing).
+/// * Make the character [tumble](Self::tumble).
+/// * [Bounce](Self::ground_bounce) the character off the ground when it lands.
+/// * Allow other actions to cancel it during a [recovery window](Self::recovery_window).
 #[derive(Clone, Debug)]
 pub struct TnuaBuiltinKnockback {
     /// Initial impulse to apply to the character before the Pushover stage starts.
@@ -62,6 +71,63 @@ pub struct TnuaBuiltinKnockback {
     /// single frame. It is useful for when the knockback animation needs to be aligned with the
     /// knockback direction.
     pub force_forward: Option<Dir3>,
+
+    /// A duration, in seconds, after the shove during which the character's input is ignored.
+    ///
+    /// During the hit-stun the basis cannot move or turn the character, and the knockback will not
+    /// end even if the Pushover boundary gets cleared.
+    pub hit_stun_duration: Float,
+
+    /// The deceleration applied to the character when it is on the ground during the hit-stun,
+    /// instead of the basis' own braking.
+    pub hit_stun_ground_deceleration: Float,
+
+    /// A multiplier for the [`shove`](Self::shove), e.g. based on the damage the character took
+    /// or on its weight.
+    pub launch_multiplier: Float,
+
+    /// If set, the [`shove`](Self::shove) is aimed at this angle (in radians) above the horizontal
+    /// plane, in the horizontal direction of the shove.
+    ///
+    /// The magnitude of the shove is not changed.
+    pub launch_angle: Option<Float>,
+
+    /// When the shove launches the character upward, keep the knockback going until it lands,
+    /// and don't let the basis pull it back down in the meantime.
+    ///
+    /// When not set, the knockback ends once the Pushover boundary is cleared, even if the
+    /// character is still in the air.
+    pub until_landing: bool,
+
+    /// Let the character tumble during the knockback instead of keeping it upright.
+    ///
+    /// Note that the rotation of the character must not be locked by the physics backend for
+    /// tumbling to have an effect. The basis will turn the character back upright after the
+    /// knockback ends.
+    pub tumble: bool,
+
+    /// The angular speed, in radians per second, given to a [tumbling](Self::tumble) character by
+    /// the shove.
+    ///
+    /// The character spins so that its top moves in the horizontal direction of the shove.
+    pub tumble_spin: Float,
+
+    /// If set, the character bounces off the ground when it lands during the knockback, keeping
+    /// this fraction of its vertical speed.
+    ///
+    /// After bouncing, the knockback lasts until the character lands again, as if
+    /// [`until_landing`](Self::until_landing) was set.
+    pub ground_bounce: Option<Float>,
+
+    /// The minimal vertical speed the character needs to land at for
+    /// [bouncing](Self::ground_bounce) off the ground.
+    pub ground_bounce_min_speed: Float,
+
+    /// A duration, in seconds, after the [hit-stun](Self::hit_stun_duration) during which other
+    /// actions (e.g. a jump) can cancel the knockback.
+    ///
+    /// Set to infinity to allow cancelling the knockback any time after the hit-stun.
+    pub recovery_window: Float,
 }
 
 impl Default for TnuaBuiltinKnockback {
@@ -73,10 +139,43 @@ impl Default for TnuaBuiltinKnockback {
             acceleration_limit: 3.0,
             air_acceleration_limit: 1.0,
             force_forward: None,
+            hit_stun_duration: 0.0,
+            hit_stun_ground_deceleration: 20.0,
+            launch_multiplier: 1.0,
+            launch_angle: None,
+            until_landing: false,
+            tumble: false,
+            tumble_spin: 0.0,
+            ground_bounce: None,
+            ground_bounce_min_speed: 3.0,
+            recovery_window: 0.0,
         }
     }
 }
 
+impl TnuaBuiltinKnockback {
+    /// The impulse applied to the character, after applying the
+    /// [`launch_multiplier`](Self::launch_multiplier) and the [`launch_angle`](Self::launch_angle)
+    /// to the [`shove`](Self::shove).
+    pub fn launch_shove(&self, up: Dir3) -> Vector3 {
+        let shove = self.launch_multiplier * self.shove;
+        let Some(launch_angle) = self.launch_angle else {
+            return shove;
+        };
+        let up = up.adjust_precision();
+        let Some(horizontal_direction) = shove.reject_from(up).try_normalize() else {
+            return shove;
+        };
+        shove.length() * (launch_angle.cos() * horizontal_direction + launch_angle.sin() * up)
+    }
+
+    fn has_landed(ctx: &TnuaActionContext) -> bool {
+        ctx.basis.displacement().is_some_and(|displacement| {
+            displacement.dot(ctx.up_direction.adjust_precision()) <= 0.0
+        })
+    }
+}
+
 impl TnuaAction for TnuaBuiltinKnockback {
     const NAME: &'static str = "TnuaBuiltinKnockback";
     type State = TnuaBuiltinKnockbackState;
@@ -86,25 +185,62 @@ impl TnuaAction for TnuaBuiltinKnockback {
         &self,
         state: &mut Self::State,
         ctx: TnuaActionContext,
-        _lifecycle_status: TnuaActionLifecycleStatus,
+        lifecycle_status: TnuaActionLifecycleStatus,
         motor: &mut TnuaMotor,
     ) -> TnuaActionLifecycleDirective {
+        let up = ctx.up_direction.adjust_precision();
+        let mut tumble_spin_boost = Vector3::ZERO;
         match state {
             TnuaBuiltinKnockbackState::Shove => {
+                let shove = self.launch_shove(ctx.up_direction);
                 let Some(boundary) = VelocityBoundary::new(
                     ctx.tracker.velocity,
-                    ctx.tracker.velocity + self.shove,
+                    ctx.tracker.velocity + shove,
                     self.no_push_timeout,
                 ) else {
                     return TnuaActionLifecycleDirective::Finished;
                 };
-                motor.lin += TnuaVelChange::boost(self.shove);
-                *state = TnuaBuiltinKnockbackState::Pushback { boundary };
+                motor.lin += TnuaVelChange::boost(shove);
+                if self.tumble {
+                    if let Some(spin_axis) = up.cross(shove).try_normalize() {
+                        tumble_spin_boost = self.tumble_spin * spin_axis - ctx.tracker.angvel;
+                    }
+                }
+                *state = TnuaBuiltinKnockbackState::Pushback {
+                    boundary,
+                    elapsed: 0.0,
+                    in_hit_stun: 0.0 < self.hit_stun_duration,
+                    can_recover: self.hit_stun_duration <= 0.0 && 0.0 < self.recovery_window,
+                    launched: self.until_landing && 0.0 < shove.dot(up),
+                    was_airborne: !Self::has_landed(&ctx),
+                    ground_bounces: 0,
+                };
             }
-            TnuaBuiltinKnockbackState::Pushback { boundary } => {
+            TnuaBuiltinKnockbackState::Pushback {
+                boundary,
+                elapsed,
+                in_hit_stun,
+                can_recover,
+                launched,
+                was_airborne,
+                ground_bounces,
+            } => {
+                *elapsed += ctx.frame_duration;
+                *in_hit_stun = *elapsed < self.hit_stun_duration;
+                *can_recover =
+                    !*in_hit_stun && *elapsed < self.hit_stun_duration + self.recovery_window;
+
+                if *can_recover
+                    && matches!(lifecycle_status, TnuaActionLifecycleStatus::CancelledInto)
+                {
+                    return TnuaActionLifecycleDirective::Finished;
+                }
+
                 boundary.update(ctx.tracker.velocity, ctx.frame_duration_as_duration());
                 if boundary.is_cleared() {
-                    return TnuaActionLifecycleDirective::Finished;
+                    if !*in_hit_stun && !*launched {
+                        return TnuaActionLifecycleDirective::Finished;
+                    }
                 } else if let Some((component_direction, component_limit)) = boundary
                     .calc_boost_part_on_boundary_axis_after_limit(
                         ctx.tracker.velocity,
@@ -119,13 +255,53 @@ impl TnuaAction for TnuaBuiltinKnockback {
                         component_limit,
                     );
                 }
+
+                let is_airborne = !Self::has_landed(&ctx);
+                let vertical_speed = ctx.tracker.velocity.dot(up);
+
+                if *launched {
+                    if is_airborne || 0.0 < vertical_speed {
+                        // Don't let the basis pull the character back to the ground
+                        motor.lin.cancel_on_axis(up);
+                    } else {
+                        *launched = false;
+                    }
+                }
+
+                if *in_hit_stun {
+                    // Ignore the horizontal movement of the basis, which follows the input
+                    motor.lin.boost = motor.lin.boost.project_onto(up);
+                    motor.lin.acceleration = motor.lin.acceleration.project_onto(up);
+                    if !is_airborne {
+                        let horizontal_velocity = ctx.tracker.velocity.reject_from(up);
+                        motor.lin += TnuaVelChange::boost(-horizontal_velocity.clamp_length_max(
+                            ctx.frame_duration * self.hit_stun_ground_deceleration,
+                        ));
+                    }
+                    motor.ang.cancel_on_axis(up);
+                }
+
+                if let Some(ground_bounce) = self.ground_bounce {
+                    if *was_airborne
+                        && !is_airborne
+                        && vertical_speed <= -self.ground_bounce_min_speed
+                    {
+                        motor.lin.cancel_on_axis(up);
+                        motor.lin +=
+                            TnuaVelChange::boost(-(1.0 + ground_bounce) * vertical_speed * up);
+                        *ground_bounces += 1;
+                        *launched = true;
+                    }
+                }
+                *was_airborne = is_airborne;
             }
         }
 
-        if let Some(force_forward) = self.force_forward {
-            motor
-                .ang
-                .cancel_on_axis(ctx.up_direction.adjust_precision());
+        if self.tumble {
+            // Let the character spin freely instead of keeping it upright
+            motor.ang = TnuaVelChange::boost(tumble_spin_boost);
+        } else if let Some(force_forward) = self.force_forward {
+            motor.ang.cancel_on_axis(up);
             motor.ang += ctx.turn_to_direction(force_forward, ctx.up_direction);
         }
 
@@ -149,5 +325,25 @@ pub enum TnuaBuiltinKnockbackState {
     /// Hindering the character's ability to overcome the
     /// [`Shove`](TnuaBuiltinKnockbackState::Shove) while waiting for it to overcome it despite the
     /// hindrance.
-    Pushback { boundary: VelocityBoundary },
+    Pushback {
+        boundary: VelocityBoundary,
+        /// The time, in seconds, since the shove.
+        elapsed: Float,
+        /// Whether the character is in [hit-stun](TnuaBuiltinKnockback::hit_stun_duration), and
+        /// its input is ignored.
+        in_hit_stun: bool,
+        /// Whether the character is in the [recovery
+        /// window](TnuaBuiltinKnockback::recovery_window), where other actions can cancel the
+        /// knockback.
+        can_recover: bool,
+        /// Whether the character was launched upward (with
+        /// [`until_landing`](TnuaBuiltinKnockback::until_landing)) or bounced, and the knockback
+        /// waits for it to land.
+        launched: bool,
+        /// Whether the character was in the air in the previous frame, for detecting landings.
+        was_airborne: bool,
+        /// The number of times the character [bounced](TnuaBuiltinKnockback::ground_bounce) off
+        /// the ground.
+        ground_bounces: usize,
+    },
 }
@@ -1,15 +1,34 @@
+mod charged_dash;
+mod charged_jump;
 mod climb;
 mod crouch;
 mod dash;
+mod dodge_roll;
 mod jump;
+mod jump_to;
 mod knockback;
 mod walk;
+mod wall_run;
 mod wall_slide;
 
-pub use climb::{TnuaBuiltinClimb, TnuaBuiltinClimbState};
+pub use charged_dash::{TnuaBuiltinChargedDash, TnuaBuiltinChargedDashState};
+pub use charged_jump::{TnuaBuiltinChargedJump, TnuaBuiltinChargedJumpState};
+pub use climb::{
+    TnuaBuiltinClimb, TnuaBuiltinClimbLadder, TnuaBuiltinClimbPole, TnuaBuiltinClimbState,
+    TnuaBuiltinClimbVariant,
+};
 pub use crouch::{TnuaBuiltinCrouch, TnuaBuiltinCrouchState};
 pub use dash::{TnuaBuiltinDash, TnuaBuiltinDashState};
-pub use jump::{TnuaBuiltinJump, TnuaBuiltinJumpState};
+pub use dodge_roll::{TnuaBuiltinDodgeRoll, TnuaBuiltinDodgeRollPhase, TnuaBuiltinDodgeRollState};
+pub use jump::{
+    TnuaBuiltinJump, TnuaBuiltinJumpState, TnuaBuiltinJumpTrajectory,
+    TnuaBuiltinJumpTrajectorySample,
+};
+pub use jump_to::{TnuaBuiltinJumpTo, TnuaBuiltinJumpToArc, TnuaBuiltinJumpToState};
 pub use knockback::{TnuaBuiltinKnockback, TnuaBuiltinKnockbackState};
-pub use walk::{TnuaBuiltinWalk, TnuaBuiltinWalkState};
+pub use walk::{
+    TnuaBuiltinWalk, TnuaBuiltinWalkDirectionalFactors, TnuaBuiltinWalkDynamicContact,
+    TnuaBuiltinWalkHeadingLocomotion, TnuaBuiltinWalkState, TnuaBuiltinWalkTurningPhase,
+};
+pub use wall_run::{TnuaBuiltinWallRun, TnuaBuiltinWallRunState};
 pub use wall_slide::{TnuaBuiltinWallSlide, TnuaBuiltinWallSlideState};
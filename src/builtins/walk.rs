@@ -1,12 +1,16 @@
 use std::time::Duration;
 
-use crate::math::{float_consts, AdjustPrecision, AsF32, Float, Quaternion, Vector3};
+use crate::math::{float_consts, AdjustPrecision, AsF32, Float, Quaternion, Vector2, Vector3};
 use bevy::prelude::*;
 
 use crate::util::rotation_arc_around_axis;
 use crate::TnuaBasisContext;
 use crate::{TnuaBasis, TnuaVelChange};
 
+/// Dynamic contacts whose direction has a smaller component perpendicular to the up direction are
+/// considered to be above or below the character, and do not push it or get pushed by it.
+const MIN_PLANAR_CONTACT_DIRECTION: Float = 0.1;
+
 /// The most common [basis](TnuaBasis) - walk around as a floating capsule.
 ///
 /// This basis implements the floating capsule character controller explained in
@@ -45,6 +49,37 @@ pub struct TnuaBuiltinWalk {
     /// Tnua assumes that this vector is orthogonal to the up direction.
     pub desired_forward: Option<Dir3>,
 
+    /// If set, Tnua will rotate the character to face this position, overriding
+    /// [`desired_forward`](Self::desired_forward).
+    ///
+    /// This is meant for lock-on (or strafing) modes, where the character keeps facing its target
+    /// while moving in any direction. The basis cannot track entities by itself, so when locking
+    /// on an entity its position should be fed every frame. If the position is directly above or
+    /// below the character, `desired_forward` is used instead.
+    pub facing_target: Option<Vector3>,
+
+    /// Factors for the speed of the [`desired_velocity`](Self::desired_velocity), based on the
+    /// direction of the movement relative to the direction the character is facing.
+    ///
+    /// The forward and backward factors scale the part of the velocity along the facing
+    /// direction, and the sideways factor scales the part perpendicular to it.
+    pub directional_speed_factors: TnuaBuiltinWalkDirectionalFactors,
+
+    /// Factors for the [`acceleration`](Self::acceleration) and the
+    /// [`air_acceleration`](Self::air_acceleration), based on the direction of the acceleration
+    /// relative to the direction the character is facing.
+    ///
+    /// When accelerating diagonally, the factors are blended according to the angle.
+    pub directional_acceleration_factors: TnuaBuiltinWalkDirectionalFactors,
+
+    /// If set, the character can only accelerate in the direction it is facing, and needs to turn
+    /// before moving in a different direction.
+    ///
+    /// When this is set and [`desired_forward`](Self::desired_forward) is `None`, the character
+    /// will turn toward the direction of the [`desired_velocity`](Self::desired_velocity). See
+    /// [`TnuaBuiltinWalkHeadingLocomotion`] for details.
+    pub heading_locomotion: Option<TnuaBuiltinWalkHeadingLocomotion>,
+
     /// The height at which the character will float above ground at rest.
     ///
     /// Note that this is the height of the character's center of mass - not the distance from its
@@ -102,6 +137,21 @@ pub struct TnuaBuiltinWalk {
     /// the jump button.
     pub free_fall_extra_gravity: Float,
 
+    /// Start braking a frame before landing, so that the character reaches the float height
+    /// exactly instead of sinking below it and bouncing back up.
+    ///
+    /// When the character is in the air and the proximity sensor shows it is going to pass the
+    /// float height during the current frame, its fall is slowed down so that it reaches the float
+    /// height at the end of the frame. This also applies while falling at the end of a
+    /// [`TnuaBuiltinJump`](crate::builtins::TnuaBuiltinJump).
+    ///
+    /// A character falling fast enough may pass the entire range of the proximity sensor in a
+    /// single frame. To prevent that, add
+    /// [`TnuaPredictiveSensorCast`](crate::TnuaPredictiveSensorCast) to the character entity, so
+    /// that the physics backend will extend the sensor's range by the distance the character is
+    /// expected to fall during the frame.
+    pub landing_prediction: bool,
+
     /// The maximum angular velocity used for keeping the character standing upright.
     ///
     /// NOTE: The character's rotation can also be locked to prevent it from being tilted, in which
@@ -117,8 +167,253 @@ pub struct TnuaBuiltinWalk {
     /// The maximum angular velocity used for turning the character when the direction changes.
     pub turning_angvel: Float,
 
+    /// How much of the angular velocity (around the up axis) of the platform the character stands
+    /// on will be added to the character's own turning.
+    ///
+    /// At `1.0`, a character standing on a spinning platform will spin together with it, keeping
+    /// its facing relative to the platform. At `0.0`, the character will keep its facing in world
+    /// space, and the platform will spin under it.
+    ///
+    /// Note that [`desired_forward`](Self::desired_forward) is in world space, so if it is fed
+    /// while the character stands on a spinning platform the character will keep facing that
+    /// world direction (the inherited rotation is compensated for, so it does not lag behind or
+    /// overshoot it). To keep the facing relative to the platform, set `desired_forward` to `None`
+    /// when there is no input that requires the character to turn.
+    ///
+    /// Defaults to `0.0`, which keeps the facing in world space.
+    pub platform_angvel_inheritance: Float,
+
+    /// How much of the velocity of the platform the character stands on will be kept when walking
+    /// off its edge.
+    ///
+    /// The platform velocity includes the tangential velocity caused by the platform's rotation.
+    /// Only the part perpendicular to the up direction is affected. At `1.0` the character keeps
+    /// all of it, and at `0.0` the character loses it immediately and only keeps its own running
+    /// velocity.
+    pub platform_velocity_inheritance: Float,
+
+    /// Like [`platform_velocity_inheritance`](Self::platform_velocity_inheritance), but used when
+    /// the character leaves the platform while performing an action that violates coyote time -
+    /// e.g. when jumping off it with [`TnuaBuiltinJump`](crate::builtins::TnuaBuiltinJump).
+    pub launch_platform_velocity_inheritance: Float,
+
+    /// The rate, in units per second squared, at which the inherited platform velocity decays
+    /// while in the air.
+    ///
+    /// Whatever is left of the inherited velocity is treated as the frame of reference for
+    /// [`desired_velocity`](Self::desired_velocity) in the air, so that the character keeps moving
+    /// with it instead of having the [`air_acceleration`](Self::air_acceleration) fight it. Set to
+    /// [`Float::INFINITY`] to make it decay immediately, leaving the air acceleration to bring the
+    /// character to the desired velocity.
+    ///
+    /// With the default settings (full inheritance and immediate decay) the velocity of whatever
+    /// the proximity sensor detects below the airborne character is used as the frame of
+    /// reference instead.
+    pub inherited_velocity_decay: Float,
+
     /// The maximum slope, in radians, that the character can stand on without slipping.
     pub max_slope: Float,
+
+    /// The dynamic rigid bodies the character is currently touching.
+    ///
+    /// Tnua cannot detect these by itself - they should be fed every frame from the user control
+    /// system, typically using
+    /// [`TnuaRadarLens::dynamic_contacts`](crate::radar_lens::TnuaRadarLens::dynamic_contacts).
+    /// Leaving this empty means that the character will push and resist dynamic bodies with its
+    /// full acceleration. Contacts that are (almost) directly above or below the character are
+    /// ignored.
+    pub dynamic_contacts: Vec<TnuaBuiltinWalkDynamicContact>,
+
+    /// How hard the character can push dynamic bodies.
+    ///
+    /// When walking into a body from [`dynamic_contacts`](Self::dynamic_contacts), the
+    /// acceleration toward it is limited to `push_strength` divided by the body's
+    /// [`relative_mass`](TnuaBuiltinWalkDynamicContact::relative_mass) - so this is the
+    /// acceleration the character can impart on a body as heavy as itself.
+    pub push_strength: Float,
+
+    /// How much the character yields when shoved by a heavier dynamic body, instead of fighting
+    /// it.
+    ///
+    /// At `0.0` the character resists the shove with its full acceleration. At `1.0` the
+    /// character gives way to a much heavier body, moving away from it at the speed the body
+    /// approaches. Bodies that are not heavier than the character are always resisted.
+    pub shove_yield: Float,
+}
+
+/// Factors for movement in different directions relative to the direction the character is
+/// facing, for [`TnuaBuiltinWalk::directional_speed_factors`] and
+/// [`TnuaBuiltinWalk::directional_acceleration_factors`].
+#[derive(Clone, Copy, Debug)]
+pub struct TnuaBuiltinWalkDirectionalFactors {
+    /// The factor for moving in the direction the character is facing.
+    pub forward: Float,
+    /// The factor for moving away from the direction the character is facing.
+    pub backward: Float,
+    /// The factor for moving perpendicular to the direction the character is facing.
+    pub sideways: Float,
+}
+
+impl Default for TnuaBuiltinWalkDirectionalFactors {
+    fn default() -> Self {
+        Self {
+            forward: 1.0,
+            backward: 1.0,
+            sideways: 1.0,
+        }
+    }
+}
+
+impl TnuaBuiltinWalkDirectionalFactors {
+    /// Scale a vector by the factors, relative to the given facing direction.
+    ///
+    /// Both the vector and the facing direction are assumed to be perpendicular to the up
+    /// direction.
+    pub fn scale(&self, vector: Vector3, forward: Vector3) -> Vector3 {
+        let forward_part = vector.dot(forward);
+        let sideways_part = vector - forward_part * forward;
+        let forward_factor = if 0.0 <= forward_part {
+            self.forward
+        } else {
+            self.backward
+        };
+        forward_factor * forward_part * forward + self.sideways * sideways_part
+    }
+
+    /// The factor for moving in the given direction, relative to the given facing direction.
+    ///
+    /// The factors are blended by the squared cosine of the angle, so that diagonal movement
+    /// gets a factor between the factors of the axes it is between.
+    pub fn factor_for_direction(&self, direction: Vector3, forward: Vector3) -> Float {
+        let Some(direction) = direction.try_normalize() else {
+            return 1.0;
+        };
+        let forward_part = direction.dot(forward);
+        let forward_factor = if 0.0 <= forward_part {
+            self.forward
+        } else {
+            self.backward
+        };
+        let forward_weight = forward_part.powi(2).min(1.0);
+        forward_weight * forward_factor + (1.0 - forward_weight) * self.sideways
+    }
+}
+
+/// Configuration for [`TnuaBuiltinWalk::heading_locomotion`], where the character moves like a
+/// vehicle (or a realistic human) instead of changing its velocity direction freely.
+///
+/// The velocity the character accelerates to is the part of the
+/// [`desired_velocity`](TnuaBuiltinWalk::desired_velocity) along the direction the character is
+/// facing, so the character cannot walk backward or sideways and changes its direction of movement
+/// by turning. The faster the character moves, the wider its turns get.
+///
+/// When the desired direction is too far from the direction the character is facing, the
+/// character stops and turns in place (or pivots, if it was moving fast) before moving again. The
+/// current phase is reported in [`TnuaBuiltinWalkState::turning_phase`].
+#[derive(Clone, Debug)]
+pub struct TnuaBuiltinWalkHeadingLocomotion {
+    /// The maximum acceleration, perpendicular to the movement, used for turning while moving.
+    ///
+    /// The angular velocity of the turn is limited so that the turning radius at a given speed is
+    /// that speed squared divided by this acceleration. At low speeds, the angular velocity is
+    /// limited by [`turning_angvel`](TnuaBuiltinWalk::turning_angvel) instead.
+    pub max_turning_acceleration: Float,
+
+    /// The angle, in radians, between the direction the character is facing and the desired
+    /// direction above which the character stops moving and turns in place.
+    pub turn_in_place_angle: Float,
+
+    /// When the character needs to turn in place while moving faster than this speed, it will
+    /// pivot instead.
+    ///
+    /// While pivoting the character brakes with [`pivot_braking`](Self::pivot_braking) and turns
+    /// with [`pivot_angvel`](Self::pivot_angvel) at the same time, ignoring the speed based limit
+    /// of [`max_turning_acceleration`](Self::max_turning_acceleration).
+    pub pivot_speed: Float,
+
+    /// The acceleration used for braking while pivoting, instead of
+    /// [`acceleration`](TnuaBuiltinWalk::acceleration).
+    ///
+    /// This should usually be higher than the regular acceleration, so that the character plants
+    /// its feet and stops quickly instead of sliding through the turn.
+    pub pivot_braking: Float,
+
+    /// The angular velocity used for turning while pivoting, instead of
+    /// [`turning_angvel`](TnuaBuiltinWalk::turning_angvel).
+    pub pivot_angvel: Float,
+}
+
+impl Default for TnuaBuiltinWalkHeadingLocomotion {
+    fn default() -> Self {
+        Self {
+            max_turning_acceleration: 30.0,
+            turn_in_place_angle: float_consts::FRAC_PI_2,
+            pivot_speed: 3.0,
+            pivot_braking: 120.0,
+            pivot_angvel: 20.0,
+        }
+    }
+}
+
+/// The turning phase of a character using [`TnuaBuiltinWalk::heading_locomotion`].
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TnuaBuiltinWalkTurningPhase {
+    /// The character moves (or stands) normally, steering toward the desired direction.
+    ///
+    /// This is always the phase when [`heading_locomotion`](TnuaBuiltinWalk::heading_locomotion)
+    /// is not set.
+    #[default]
+    Steering,
+    /// The desired direction is too far from the direction the character is facing, so it stops
+    /// and turns in place with the regular [`acceleration`](TnuaBuiltinWalk::acceleration) and
+    /// [`turning_angvel`](TnuaBuiltinWalk::turning_angvel).
+    TurningInPlace,
+    /// Like [`TurningInPlace`](Self::TurningInPlace), but the character was moving fast so it
+    /// brakes hard while turning, using
+    /// [`pivot_braking`](TnuaBuiltinWalkHeadingLocomotion::pivot_braking) and
+    /// [`pivot_angvel`](TnuaBuiltinWalkHeadingLocomotion::pivot_angvel). Once the character slows
+    /// down below [`pivot_speed`](TnuaBuiltinWalkHeadingLocomotion::pivot_speed) the phase
+    /// changes to [`TurningInPlace`](Self::TurningInPlace).
+    Pivoting,
+}
+
+impl TnuaBuiltinWalkHeadingLocomotion {
+    fn restrict_desired_velocity(
+        &self,
+        desired_velocity: Vector3,
+        desired_heading: Option<Vector3>,
+        facing: Vector3,
+        current_speed: Float,
+    ) -> (Vector3, TnuaBuiltinWalkTurningPhase) {
+        if let Some(desired_heading) = desired_heading {
+            if self.turn_in_place_angle < facing.angle_between(desired_heading) {
+                let turning_phase = if self.pivot_speed < current_speed {
+                    TnuaBuiltinWalkTurningPhase::Pivoting
+                } else {
+                    TnuaBuiltinWalkTurningPhase::TurningInPlace
+                };
+                return (Vector3::ZERO, turning_phase);
+            }
+        }
+        (
+            desired_velocity.dot(facing).max(0.0) * facing,
+            TnuaBuiltinWalkTurningPhase::Steering,
+        )
+    }
+}
+
+/// A dynamic rigid body in contact with the character, for
+/// [`TnuaBuiltinWalk::dynamic_contacts`].
+#[derive(Clone, Debug)]
+pub struct TnuaBuiltinWalkDynamicContact {
+    /// The entity of the body's rigid body.
+    pub entity: Entity,
+    /// The direction from the character to the point of contact.
+    pub direction: Dir3,
+    /// The mass of the body divided by the mass of the character.
+    pub relative_mass: Float,
+    /// The linear velocity of the body.
+    pub linvel: Vector3,
 }
 
 impl Default for TnuaBuiltinWalk {
@@ -126,6 +421,10 @@ impl Default for TnuaBuiltinWalk {
         Self {
             desired_velocity: Vector3::ZERO,
             desired_forward: None,
+            facing_target: None,
+            directional_speed_factors: Default::default(),
+            directional_acceleration_factors: Default::default(),
+            heading_locomotion: None,
             float_height: 0.0,
             cling_distance: 1.0,
             spring_strength: 400.0,
@@ -134,10 +433,18 @@ impl Default for TnuaBuiltinWalk {
             air_acceleration: 20.0,
             coyote_time: 0.15,
             free_fall_extra_gravity: 60.0,
+            landing_prediction: false,
             tilt_offset_angvel: 5.0,
             tilt_offset_angacl: 500.0,
             turning_angvel: 10.0,
+            platform_angvel_inheritance: 0.0,
+            platform_velocity_inheritance: 1.0,
+            launch_platform_velocity_inheritance: 1.0,
+            inherited_velocity_decay: Float::INFINITY,
             max_slope: float_consts::FRAC_PI_2,
+            dynamic_contacts: Vec::new(),
+            push_strength: Float::INFINITY,
+            shove_yield: 0.0,
         }
     }
 }
@@ -152,9 +459,12 @@ impl TnuaBasis for TnuaBuiltinWalk {
             stopwatch.tick(Duration::from_secs_f64(ctx.frame_duration as f64));
         }
 
+        let launched_by_action = std::mem::take(&mut state.launched_by_action);
+        let previously_standing_on = state.standing_on.clone();
+
         let climb_vectors: Option<ClimbVectors>;
         let considered_in_air: bool;
-        let impulse_to_offset: Vector3;
+        let mut impulse_to_offset: Vector3;
         let slipping_vector: Option<Vector3>;
 
         if let Some(sensor_output) = &ctx.proximity_sensor.output {
@@ -227,26 +537,114 @@ impl TnuaBasis for TnuaBuiltinWalk {
             slipping_vector = None;
             state.standing_on = None;
         }
+
+        if state.standing_on.is_some() {
+            state.inherited_velocity = Vector3::ZERO;
+        } else {
+            if let Some(previously_standing_on) = &previously_standing_on {
+                let platform_velocity = previously_standing_on
+                    .entity_linvel
+                    .reject_from(ctx.up_direction.adjust_precision());
+                let inheritance = if launched_by_action {
+                    self.launch_platform_velocity_inheritance
+                } else {
+                    self.platform_velocity_inheritance
+                };
+                state.inherited_velocity = inheritance * platform_velocity;
+                impulse_to_offset += state.inherited_velocity - platform_velocity;
+            }
+            let inherited_speed = state.inherited_velocity.length();
+            state.inherited_velocity = state.inherited_velocity.clamp_length_max(
+                (inherited_speed - ctx.frame_duration * self.inherited_velocity_decay).max(0.0),
+            );
+        }
+        if state.standing_on.is_none() && self.configures_velocity_inheritance() {
+            // In the air the inherited velocity replaces the platform below as the frame of
+            // reference for the horizontal movement, even if the sensor still detects it.
+            state.effective_velocity = ctx.tracker.velocity - state.inherited_velocity;
+            if let Some(sensor_output) = &ctx.proximity_sensor.output {
+                state.effective_velocity -= sensor_output
+                    .entity_linvel
+                    .project_onto(ctx.up_direction.adjust_precision());
+            }
+        }
         state.effective_velocity += impulse_to_offset;
 
         let velocity_on_plane = state
             .effective_velocity
             .reject_from(ctx.up_direction.adjust_precision());
 
-        let desired_boost = self.desired_velocity - velocity_on_plane;
+        let facing = ctx
+            .tracker
+            .rotation
+            .mul_vec3(Vector3::NEG_Z)
+            .reject_from(ctx.up_direction.adjust_precision())
+            .try_normalize();
+
+        let desired_forward = self
+            .facing_target
+            .and_then(|facing_target| {
+                (facing_target - ctx.tracker.translation)
+                    .reject_from(ctx.up_direction.adjust_precision())
+                    .try_normalize()
+            })
+            .or_else(|| Some(self.desired_forward?.adjust_precision()))
+            .or_else(|| {
+                if self.heading_locomotion.is_some() {
+                    self.desired_velocity
+                        .reject_from(ctx.up_direction.adjust_precision())
+                        .try_normalize()
+                } else {
+                    None
+                }
+            });
 
-        let safe_direction_coefficient = self
-            .desired_velocity
+        let desired_velocity = if let Some(facing) = facing {
+            self.directional_speed_factors
+                .scale(self.desired_velocity, facing)
+        } else {
+            self.desired_velocity
+        };
+        let desired_velocity = match (&self.heading_locomotion, facing) {
+            (Some(heading_locomotion), Some(facing)) => {
+                let (desired_velocity, turning_phase) = heading_locomotion
+                    .restrict_desired_velocity(
+                        desired_velocity,
+                        desired_forward,
+                        facing,
+                        velocity_on_plane.length(),
+                    );
+                state.turning_phase = turning_phase;
+                desired_velocity
+            }
+            _ => {
+                state.turning_phase = TnuaBuiltinWalkTurningPhase::Steering;
+                desired_velocity
+            }
+        };
+        let desired_velocity = self.yield_to_shoves(&ctx, desired_velocity);
+        let desired_boost = desired_velocity - velocity_on_plane;
+
+        let safe_direction_coefficient = desired_velocity
             .normalize_or_zero()
             .dot(velocity_on_plane.normalize_or_zero());
         let direction_change_factor = 1.5 - 0.5 * safe_direction_coefficient;
 
-        let relevant_acceleration_limit = if considered_in_air {
-            self.air_acceleration
+        let relevant_acceleration_limit = match (&self.heading_locomotion, state.turning_phase) {
+            _ if considered_in_air => self.air_acceleration,
+            (Some(heading_locomotion), TnuaBuiltinWalkTurningPhase::Pivoting) => {
+                heading_locomotion.pivot_braking
+            }
+            _ => self.acceleration,
+        };
+        let directional_acceleration_factor = if let Some(facing) = facing {
+            self.directional_acceleration_factors
+                .factor_for_direction(desired_boost, facing)
         } else {
-            self.acceleration
+            1.0
         };
-        let max_acceleration = direction_change_factor * relevant_acceleration_limit;
+        let max_acceleration =
+            direction_change_factor * directional_acceleration_factor * relevant_acceleration_limit;
 
         state.vertical_velocity = if let Some(climb_vectors) = &climb_vectors {
             state.effective_velocity.dot(climb_vectors.direction)
@@ -257,8 +655,7 @@ impl TnuaBasis for TnuaBuiltinWalk {
             0.0
         };
 
-        let walk_vel_change = if self.desired_velocity == Vector3::ZERO && slipping_vector.is_none()
-        {
+        let walk_vel_change = if desired_velocity == Vector3::ZERO && slipping_vector.is_none() {
             // When stopping, prefer a boost to be able to reach a precise stop (see issue #39)
             let walk_boost = desired_boost.clamp_length_max(ctx.frame_duration * max_acceleration);
             let walk_boost = if let Some(climb_vectors) = &climb_vectors {
@@ -266,6 +663,8 @@ impl TnuaBasis for TnuaBuiltinWalk {
             } else {
                 walk_boost
             };
+            let walk_boost =
+                self.limit_push(&ctx, walk_boost / ctx.frame_duration) * ctx.frame_duration;
             TnuaVelChange::boost(walk_boost)
         } else {
             // When accelerating, prefer an acceleration because the physics backends treat it
@@ -278,6 +677,7 @@ impl TnuaBasis for TnuaBuiltinWalk {
                 } else {
                     walk_acceleration
                 };
+            let walk_acceleration = self.limit_push(&ctx, walk_acceleration);
 
             let slipping_boost = 'slipping_boost: {
                 let Some(slipping_vector) = slipping_vector else {
@@ -349,6 +749,18 @@ impl TnuaBasis for TnuaBuiltinWalk {
                                 state.airborne_timer = None;
                                 continue;
                             }
+                            if self.landing_prediction {
+                                if let Some(braking_boost) = self.landing_braking_boost(
+                                    state,
+                                    &ctx,
+                                    sensor_output.proximity.adjust_precision(),
+                                    -self.free_fall_extra_gravity,
+                                ) {
+                                    break 'upward_impulse TnuaVelChange::boost(
+                                        braking_boost * ctx.up_direction.adjust_precision(),
+                                    );
+                                }
+                            }
                         }
                         if state.vertical_velocity <= 0.0 {
                             break 'upward_impulse TnuaVelChange::acceleration(
@@ -370,6 +782,15 @@ impl TnuaBasis for TnuaBuiltinWalk {
             + ctx.frame_duration * motor.lin.acceleration
             - impulse_to_offset;
         state.running_velocity = new_velocity.reject_from(ctx.up_direction.adjust_precision());
+        state.relative_running_velocity = if let Some(facing) = facing {
+            let right = facing.cross(ctx.up_direction.adjust_precision());
+            Vector2::new(
+                state.running_velocity.dot(right),
+                state.running_velocity.dot(facing),
+            )
+        } else {
+            Vector2::ZERO
+        };
 
         // Tilt
 
@@ -387,19 +808,47 @@ impl TnuaBasis for TnuaBuiltinWalk {
 
         // Turning
 
-        let desired_angvel = if let Some(desired_forward) = self.desired_forward {
-            let current_forward = ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z);
-            let rotation_along_up_axis = rotation_arc_around_axis(
-                ctx.up_direction,
-                current_forward,
-                desired_forward.adjust_precision(),
+        let turning_angvel = match (&self.heading_locomotion, state.turning_phase) {
+            (Some(heading_locomotion), TnuaBuiltinWalkTurningPhase::Steering) => {
+                let speed = velocity_on_plane.length();
+                if 0.0 < speed {
+                    self.turning_angvel
+                        .min(heading_locomotion.max_turning_acceleration / speed)
+                } else {
+                    self.turning_angvel
+                }
+            }
+            (Some(heading_locomotion), TnuaBuiltinWalkTurningPhase::Pivoting) => {
+                heading_locomotion.pivot_angvel
+            }
+            _ => self.turning_angvel,
+        };
+        let platform_angvel = match (&state.standing_on, &ctx.proximity_sensor.output) {
+            (Some(_), Some(sensor_output)) => {
+                self.platform_angvel_inheritance
+                    * sensor_output
+                        .entity_angvel
+                        .dot(ctx.up_direction.adjust_precision())
+            }
+            _ => 0.0,
+        };
+
+        let desired_angvel = if let Some(desired_forward) = desired_forward {
+            // Measure the turn from where the platform is going to carry the character this
+            // frame, so that the inherited rotation does not overshoot the desired direction.
+            let current_forward = Quaternion::from_axis_angle(
+                ctx.up_direction.adjust_precision(),
+                platform_angvel * ctx.frame_duration,
             )
-            .unwrap_or(0.0);
-            (rotation_along_up_axis / ctx.frame_duration)
-                .clamp(-self.turning_angvel, self.turning_angvel)
+            .mul_vec3(ctx.tracker.rotation.mul_vec3(Vector3::NEG_Z));
+            let rotation_along_up_axis =
+                rotation_arc_around_axis(ctx.up_direction, current_forward, desired_forward)
+                    .unwrap_or(0.0);
+            (rotation_along_up_axis / ctx.frame_duration).clamp(-turning_angvel, turning_angvel)
         } else {
             0.0
         };
+        let desired_angvel = desired_angvel + platform_angvel;
 
         // NOTE: This is the regular axis system so we used the configured up.
         let existing_angvel = ctx.tracker.angvel.dot(ctx.up_direction.adjust_precision());
@@ -438,6 +887,7 @@ impl TnuaBasis for TnuaBuiltinWalk {
     fn neutralize(&mut self) {
         self.desired_velocity = Vector3::ZERO;
         self.desired_forward = None;
+        self.facing_target = None;
     }
 
     fn is_airborne(&self, state: &Self::State) -> bool {
@@ -448,6 +898,7 @@ impl TnuaBasis for TnuaBuiltinWalk {
     }
 
     fn violate_coyote_time(&self, state: &mut Self::State) {
+        state.launched_by_action = true;
         if let Some(timer) = &mut state.airborne_timer {
             timer.set_duration(Duration::ZERO);
         }
@@ -483,6 +934,98 @@ impl TnuaBuiltinWalk {
             boost: ctx.up_direction.adjust_precision() * -dampening_boost,
         }
     }
+
+    /// Whether the platform velocity inheritance is configured, as opposed to the default settings
+    /// which keep the platform detected below the character as the frame of reference.
+    fn configures_velocity_inheritance(&self) -> bool {
+        self.inherited_velocity_decay.is_finite()
+            || self.platform_velocity_inheritance != 1.0
+            || self.launch_platform_velocity_inheritance != 1.0
+    }
+
+    fn planar_contact_direction(
+        ctx: &TnuaBasisContext,
+        contact: &TnuaBuiltinWalkDynamicContact,
+    ) -> Option<Vector3> {
+        let planar_direction = contact
+            .direction
+            .adjust_precision()
+            .reject_from(ctx.up_direction.adjust_precision());
+        if planar_direction.length() < MIN_PLANAR_CONTACT_DIRECTION {
+            return None;
+        }
+        planar_direction.try_normalize()
+    }
+
+    fn yield_to_shoves(&self, ctx: &TnuaBasisContext, desired_velocity: Vector3) -> Vector3 {
+        let mut desired_velocity = desired_velocity;
+        if self.shove_yield <= 0.0 {
+            return desired_velocity;
+        }
+        for contact in self.dynamic_contacts.iter() {
+            if contact.relative_mass <= 1.0 {
+                continue;
+            }
+            let Some(direction) = Self::planar_contact_direction(ctx, contact) else {
+                continue;
+            };
+            let shove_speed = -contact.linvel.dot(direction);
+            if shove_speed <= 0.0 {
+                continue;
+            }
+            let yielded_speed =
+                self.shove_yield * (1.0 - 1.0 / contact.relative_mass) * shove_speed;
+            let speed_away = -desired_velocity.dot(direction);
+            if speed_away < yielded_speed {
+                desired_velocity -= (yielded_speed - speed_away) * direction;
+            }
+        }
+        desired_velocity
+    }
+
+    /// The upward boost that will make the character reach the float height exactly at the end of
+    /// the current frame, or `None` if it is not going to reach it during this frame.
+    ///
+    /// `extra_upward_acceleration` is the vertical acceleration (on top of the gravity) that would
+    /// be applied to the falling character if it was not braking.
+    ///
+    /// Note: this is exposed so that actions like
+    /// [`TnuaBuiltinJump`](crate::builtins::TnuaBuiltinJump), which take over the vertical motion
+    /// while falling, can use the [landing prediction](Self::landing_prediction) as well.
+    pub fn landing_braking_boost(
+        &self,
+        state: &TnuaBuiltinWalkState,
+        ctx: &TnuaBasisContext,
+        proximity: Float,
+        extra_upward_acceleration: Float,
+    ) -> Option<Float> {
+        let up = ctx.up_direction.adjust_precision();
+        let upward_velocity = state.effective_velocity.dot(up);
+        let gravity = ctx.tracker.gravity.dot(up);
+        let distance_to_float_height = proximity - self.float_height;
+        let free_fall_velocity =
+            upward_velocity + (gravity + extra_upward_acceleration) * ctx.frame_duration;
+        if -free_fall_velocity * ctx.frame_duration <= distance_to_float_height {
+            return None;
+        }
+        let required_velocity = -distance_to_float_height / ctx.frame_duration;
+        Some(required_velocity - (upward_velocity + gravity * ctx.frame_duration))
+    }
+
+    fn limit_push(&self, ctx: &TnuaBasisContext, acceleration: Vector3) -> Vector3 {
+        let mut acceleration = acceleration;
+        for contact in self.dynamic_contacts.iter() {
+            let Some(direction) = Self::planar_contact_direction(ctx, contact) else {
+                continue;
+            };
+            let max_push = self.push_strength / contact.relative_mass;
+            let push = acceleration.dot(direction);
+            if max_push < push {
+                acceleration -= (push - max_push) * direction;
+            }
+        }
+        acceleration
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -505,6 +1048,23 @@ pub struct TnuaBuiltinWalkState {
     /// ([`standing_on_entity`](Self::standing_on_entity) returns `Some`) then the
     /// `running_velocity` will be relative to the velocity of that entity.
     pub running_velocity: Vector3,
+    /// The part of the velocity of the last platform the character stood on that it still carries
+    /// while in the air.
+    ///
+    /// See [`platform_velocity_inheritance`](TnuaBuiltinWalk::platform_velocity_inheritance) and
+    /// [`inherited_velocity_decay`](TnuaBuiltinWalk::inherited_velocity_decay).
+    pub inherited_velocity: Vector3,
+    /// The [`running_velocity`](Self::running_velocity) relative to the direction the character
+    /// is facing, with X being to the character's right and Y being to its front.
+    ///
+    /// Useful for animation blend spaces when strafing (e.g. with
+    /// [`facing_target`](TnuaBuiltinWalk::facing_target)). This is zero if the character's facing
+    /// direction cannot be projected on the plane perpendicular to the up direction.
+    pub relative_running_velocity: Vector2,
+    /// The turning phase of the character when using
+    /// [`heading_locomotion`](TnuaBuiltinWalk::heading_locomotion).
+    pub turning_phase: TnuaBuiltinWalkTurningPhase,
+    launched_by_action: bool,
 }
 
 impl TnuaBuiltinWalkState {
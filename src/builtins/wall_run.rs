@@ -0,0 +1,259 @@
+use crate::math::{AdjustPrecision, AsF32, Float, Vector3};
+use bevy::prelude::*;
+use bevy::time::Stopwatch;
+
+use crate::util::{calc_angular_velchange_to_force_forward, interpolate_sorted_entries};
+use crate::{
+    TnuaAction, TnuaActionContext, TnuaActionInitiationDirective, TnuaActionLifecycleDirective,
+    TnuaActionLifecycleStatus, TnuaMotor, TnuaVelChange,
+};
+
+/// An [action](TnuaAction) for running along vertical walls.
+///
+/// The action can only start while the character is in the air (typically after jumping
+/// alongside a wall detected by the [obstacle radar](crate::TnuaObstacleRadar)) and moving along
+/// the wall fast enough. Once started, the character keeps its horizontal speed along the wall
+/// while gravity is reduced according to [`gravity_factor`](Self::gravity_factor) or
+/// [`gravity_curve`](Self::gravity_curve).
+///
+/// The action must be fed every frame with the wall's current contact point and normal. It ends
+/// when it is no longer fed, when its [`duration`](Self::duration) runs out, or when it is fed
+/// with [`jump_off`](Self::jump_off) set - in which case the character jumps away from the wall.
+#[derive(Clone, Debug)]
+pub struct TnuaBuiltinWallRun {
+    /// The entity of the wall to run on.
+    pub wall_entity: Option<Entity>,
+
+    /// The point on the wall where the character touches it.
+    pub contact_point_with_wall: Vector3,
+
+    /// The wall's normal.
+    ///
+    /// Only the part of the normal perpendicular to the up direction is used.
+    pub normal: Dir3,
+
+    /// The minimal speed, along the wall, required for starting the wall run.
+    pub min_entry_speed: Float,
+
+    /// If set, the upward velocity of the character will be set to this value when the wall run
+    /// starts.
+    ///
+    /// Combined with reduced gravity, this makes the character run along the wall in an arc.
+    pub entry_upward_speed: Option<Float>,
+
+    /// The maximum acceleration used for keeping the speed along the wall.
+    pub tangent_acceleration: Float,
+
+    /// The fraction of the gravity applied to the character during the wall run.
+    ///
+    /// Ignored if [`gravity_curve`](Self::gravity_curve) is not empty.
+    pub gravity_factor: Float,
+
+    /// The fraction of the gravity applied to the character based on the time since the wall run
+    /// started.
+    ///
+    /// Each entry is a time, in seconds, and the gravity factor at that time. The entries must be
+    /// sorted by their times, and the factors between them are interpolated linearly. When empty,
+    /// [`gravity_factor`](Self::gravity_factor) is used instead.
+    pub gravity_curve: Vec<(Float, Float)>,
+
+    /// The maximum duration, in seconds, of the wall run.
+    pub duration: Float,
+
+    /// A distance to maintain from the wall.
+    ///
+    /// Specifically - the distance from
+    /// [`contact_point_with_wall`](Self::contact_point_with_wall) in the direction of the
+    /// [`normal`](Self::normal).
+    pub maintain_distance: Option<Float>,
+
+    /// Rotate the character to face the direction it runs in.
+    pub face_run_direction: bool,
+
+    /// Set to `true` to end the wall run by jumping away from the wall.
+    pub jump_off: bool,
+
+    /// The speed, in the direction of the wall's normal, of the jump off the wall.
+    pub jump_off_away_speed: Float,
+
+    /// The upward speed of the jump off the wall.
+    pub jump_off_upward_speed: Float,
+}
+
+impl Default for TnuaBuiltinWallRun {
+    fn default() -> Self {
+        Self {
+            wall_entity: None,
+            contact_point_with_wall: Vector3::ZERO,
+            normal: Dir3::X,
+            min_entry_speed: 2.0,
+            entry_upward_speed: None,
+            tangent_acceleration: 30.0,
+            gravity_factor: 0.2,
+            gravity_curve: Vec::new(),
+            duration: 1.5,
+            maintain_distance: None,
+            face_run_direction: true,
+            jump_off: false,
+            jump_off_away_speed: 6.0,
+            jump_off_upward_speed: 8.0,
+        }
+    }
+}
+
+impl TnuaBuiltinWallRun {
+    fn planar_normal(&self, up: Vector3) -> Option<Vector3> {
+        self.normal
+            .adjust_precision()
+            .reject_from(up)
+            .try_normalize()
+    }
+
+    /// The fraction of the gravity applied to the character after the wall run went on for the
+    /// given duration.
+    pub fn gravity_factor_at(&self, elapsed: Float) -> Float {
+        interpolate_sorted_entries(&self.gravity_curve, elapsed).unwrap_or(self.gravity_factor)
+    }
+}
+
+impl TnuaAction for TnuaBuiltinWallRun {
+    const NAME: &'static str = "TnuaBuiltinWallRun";
+    type State = TnuaBuiltinWallRunState;
+    const VIOLATES_COYOTE_TIME: bool = true;
+
+    fn initiation_decision(
+        &self,
+        ctx: TnuaActionContext,
+        _being_fed_for: &Stopwatch,
+    ) -> TnuaActionInitiationDirective {
+        if !ctx.basis.is_airborne() {
+            return TnuaActionInitiationDirective::Reject;
+        }
+        let up = ctx.up_direction.adjust_precision();
+        let Some(normal) = self.planar_normal(up) else {
+            return TnuaActionInitiationDirective::Reject;
+        };
+        let tangent = normal.cross(up);
+        if ctx.tracker.velocity.dot(tangent).abs() < self.min_entry_speed {
+            return TnuaActionInitiationDirective::Reject;
+        }
+        TnuaActionInitiationDirective::Allow
+    }
+
+    fn apply(
+        &self,
+        state: &mut Self::State,
+        ctx: TnuaActionContext,
+        lifecycle_status: TnuaActionLifecycleStatus,
+        motor: &mut TnuaMotor,
+    ) -> TnuaActionLifecycleDirective {
+        if !lifecycle_status.is_active() {
+            return TnuaActionLifecycleDirective::Finished;
+        }
+
+        let up = ctx.up_direction.adjust_precision();
+        let Some(normal) = self.planar_normal(up) else {
+            return TnuaActionLifecycleDirective::Finished;
+        };
+        let tangent = normal.cross(up);
+
+        let mut velocity = ctx.tracker.velocity;
+
+        if lifecycle_status.just_started() {
+            let tangent_speed = velocity.dot(tangent);
+            *state = TnuaBuiltinWallRunState::Running {
+                direction: tangent * tangent_speed.signum(),
+                speed: tangent_speed.abs().max(self.min_entry_speed),
+                elapsed: 0.0,
+            };
+            if let Some(entry_upward_speed) = self.entry_upward_speed {
+                velocity += (entry_upward_speed - velocity.dot(up)) * up;
+            }
+        }
+
+        let TnuaBuiltinWallRunState::Running {
+            direction,
+            speed,
+            elapsed,
+        } = state
+        else {
+            return TnuaActionLifecycleDirective::Finished;
+        };
+
+        // The wall may not be flat, so the direction is updated to follow the current normal.
+        let run_direction = tangent * tangent.dot(*direction).signum();
+        *direction = run_direction;
+
+        if self.jump_off {
+            let desired_velocity = velocity.dot(run_direction) * run_direction
+                + self.jump_off_away_speed * normal
+                + self.jump_off_upward_speed * up;
+            motor.lin = TnuaVelChange::boost(desired_velocity - ctx.tracker.velocity);
+            return TnuaActionLifecycleDirective::Finished;
+        }
+
+        if self.duration <= *elapsed {
+            return TnuaActionLifecycleDirective::Finished;
+        }
+
+        // Override the air control of the basis
+        motor.lin = TnuaVelChange::boost(velocity - ctx.tracker.velocity);
+
+        let current_speed = velocity.dot(run_direction);
+        let tangent_boost = (*speed - current_speed).clamp(
+            -ctx.frame_duration * self.tangent_acceleration,
+            ctx.frame_duration * self.tangent_acceleration,
+        );
+        motor.lin += TnuaVelChange::boost(tangent_boost * run_direction);
+
+        let gravity_factor = self.gravity_factor_at(*elapsed);
+        motor.lin += TnuaVelChange::acceleration((gravity_factor - 1.0) * ctx.tracker.gravity);
+
+        if let Some(maintain_distance) = self.maintain_distance {
+            let current_cling_distance =
+                (ctx.tracker.translation - self.contact_point_with_wall).dot(normal);
+            let current_cling_speed = velocity.dot(normal);
+            let desired_cling_speed =
+                (maintain_distance - current_cling_distance) / ctx.frame_duration;
+            motor.lin.cancel_on_axis(normal);
+            motor.lin += TnuaVelChange::boost((desired_cling_speed - current_cling_speed) * normal);
+        }
+
+        if self.face_run_direction {
+            if let Ok(run_direction) = Dir3::new(run_direction.f32()) {
+                motor.ang.cancel_on_axis(up);
+                motor.ang += calc_angular_velchange_to_force_forward(
+                    run_direction,
+                    ctx.tracker.rotation,
+                    ctx.tracker.angvel,
+                    ctx.up_direction,
+                    ctx.frame_duration,
+                );
+            }
+        }
+
+        *elapsed += ctx.frame_duration;
+
+        TnuaActionLifecycleDirective::StillActive
+    }
+
+    fn target_entity(&self, _state: &Self::State) -> Option<Entity> {
+        self.wall_entity
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub enum TnuaBuiltinWallRunState {
+    /// The wall run has not started yet.
+    #[default]
+    NotStarted,
+    /// The character is running along the wall.
+    Running {
+        /// The direction, along the wall, the character runs in.
+        direction: Vector3,
+        /// The speed the character maintains along the wall.
+        speed: Float,
+        /// The time, in seconds, since the wall run started.
+        elapsed: Float,
+    },
+}
@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+
+use crate::builtins::TnuaBuiltinWalk;
+use crate::math::{AdjustPrecision, AsF32, Float, Quaternion, Vector2, Vector3};
+use crate::util::interpolate_sorted_entries;
+
+/// An helper for converting raw 2D movement input (e.g. from an analog stick or from WASD keys)
+/// to a movement direction relative to the camera.
+///
+/// The input's Y axis is mapped to the direction the camera is looking at and its X axis is
+/// mapped to the camera's right, both projected on the plane perpendicular to the character's up
+/// direction. This way the character walks on the ground even when the camera is pitched up or
+/// down. The up direction would usually be the opposite of the
+/// [`TnuaRigidBodyTracker::gravity`](crate::TnuaRigidBodyTracker::gravity) of the character.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::control_helpers::TnuaCameraRelativeInput;
+/// # use bevy_tnua::math::*;
+/// # use bevy_tnua::TnuaRigidBodyTracker;
+/// # let camera_transform = GlobalTransform::default();
+/// # let stick = Vec2::ZERO;
+/// # let tracker = TnuaRigidBodyTracker::default();
+/// # let mut controller = TnuaController::default();
+/// let camera_relative_input = TnuaCameraRelativeInput {
+///     max_speed: 10.0,
+///     ..Default::default()
+/// };
+/// let movement = camera_relative_input.movement_from_camera_rotation(
+///     camera_transform.rotation().adjust_precision(),
+///     stick.adjust_precision(),
+///     Dir3::new(-tracker.gravity.f32()).unwrap_or(Dir3::Y),
+/// );
+/// let mut walk = TnuaBuiltinWalk {
+///     float_height: 1.5,
+///     ..Default::default()
+/// };
+/// movement.apply_to_walk(&mut walk);
+/// controller.basis(walk);
+/// ```
+#[derive(Component, Clone, Debug)]
+pub struct TnuaCameraRelativeInput {
+    /// Input with a magnitude below this value will be ignored.
+    pub inner_deadzone: Float,
+
+    /// Input with a magnitude above `1.0` minus this value will be considered full input.
+    pub outer_deadzone: Float,
+
+    /// How the magnitude of the input (after applying the deadzones) is mapped to the magnitude
+    /// of the movement.
+    pub response_curve: TnuaInputResponseCurve,
+
+    /// The speed of the character on full input.
+    pub max_speed: Float,
+}
+
+impl Default for TnuaCameraRelativeInput {
+    fn default() -> Self {
+        Self {
+            inner_deadzone: 0.1,
+            outer_deadzone: 0.05,
+            response_curve: TnuaInputResponseCurve::Linear,
+            max_speed: 1.0,
+        }
+    }
+}
+
+/// Maps the magnitude of the input (after applying the deadzones) to the magnitude of the
+/// movement. Both are between `0.0` and `1.0`.
+#[derive(Clone, Debug)]
+pub enum TnuaInputResponseCurve {
+    /// Use the magnitude of the input as is.
+    Linear,
+    /// Raise the magnitude of the input to the given power.
+    ///
+    /// Values above `1.0` give finer control at small inputs, values below `1.0` make the
+    /// character reach high speeds faster.
+    Power(Float),
+    /// Linearly interpolate between `(input, output)` entries, sorted by their inputs.
+    Entries(Vec<(Float, Float)>),
+}
+
+impl TnuaInputResponseCurve {
+    /// Map the magnitude of the input to the magnitude of the movement.
+    pub fn apply(&self, magnitude: Float) -> Float {
+        match self {
+            TnuaInputResponseCurve::Linear => magnitude,
+            TnuaInputResponseCurve::Power(exponent) => magnitude.powf(*exponent),
+            TnuaInputResponseCurve::Entries(entries) => {
+                interpolate_sorted_entries(entries, magnitude).unwrap_or(magnitude)
+            }
+        }
+        .clamp(0.0, 1.0)
+    }
+}
+
+/// The result of [`TnuaCameraRelativeInput`].
+#[derive(Clone, Debug, Default)]
+pub struct TnuaCameraRelativeMovement {
+    /// The direction of the movement, scaled between `0.0` and `1.0` based on the input.
+    pub direction: Vector3,
+    /// The velocity to feed to [`TnuaBuiltinWalk::desired_velocity`].
+    pub desired_velocity: Vector3,
+    /// The direction of the movement, or `None` if there is no movement.
+    ///
+    /// Can be fed to [`TnuaBuiltinWalk::desired_forward`] to make the character face the
+    /// direction it walks to.
+    pub desired_forward: Option<Dir3>,
+    /// The direction the camera is looking at, projected on the plane perpendicular to the up
+    /// direction.
+    ///
+    /// Can be fed to [`TnuaBuiltinWalk::desired_forward`] instead of
+    /// [`desired_forward`](Self::desired_forward) to make the character face where the camera is
+    /// looking.
+    pub camera_forward: Option<Dir3>,
+}
+
+impl TnuaCameraRelativeMovement {
+    /// Set the [`desired_velocity`](TnuaBuiltinWalk::desired_velocity) and
+    /// [`desired_forward`](TnuaBuiltinWalk::desired_forward) of a walk basis.
+    ///
+    /// If there is no movement, the previous `desired_forward` of the walk basis is kept.
+    pub fn apply_to_walk(&self, walk: &mut TnuaBuiltinWalk) {
+        walk.desired_velocity = self.desired_velocity;
+        if self.desired_forward.is_some() {
+            walk.desired_forward = self.desired_forward;
+        }
+    }
+}
+
+impl TnuaCameraRelativeInput {
+    /// Apply the deadzones and the response curve to the magnitude of the input.
+    pub fn input_magnitude(&self, input: Vector2) -> Float {
+        let magnitude = input.length();
+        if magnitude <= self.inner_deadzone {
+            return 0.0;
+        }
+        let live_range = 1.0 - self.inner_deadzone - self.outer_deadzone;
+        let magnitude = if 0.0 < live_range {
+            ((magnitude - self.inner_deadzone) / live_range).min(1.0)
+        } else {
+            1.0
+        };
+        self.response_curve.apply(magnitude)
+    }
+
+    /// Calculate the movement relative to a camera with the given rotation.
+    ///
+    /// The rotation would usually be taken from the `GlobalTransform` of the camera.
+    pub fn movement_from_camera_rotation(
+        &self,
+        camera_rotation: Quaternion,
+        input: Vector2,
+        up_direction: Dir3,
+    ) -> TnuaCameraRelativeMovement {
+        let up = up_direction.adjust_precision();
+        let camera_forward = camera_rotation.mul_vec3(Vector3::NEG_Z);
+        // When the camera looks straight up or down its forward direction cannot be projected,
+        // but the top of the screen still points where the player would expect "forward" to be.
+        let forward = camera_forward
+            .reject_from(up)
+            .try_normalize()
+            .or_else(|| {
+                let camera_up = camera_rotation.mul_vec3(Vector3::Y);
+                (camera_up * -camera_forward.dot(up).signum())
+                    .reject_from(up)
+                    .try_normalize()
+            })
+            .unwrap_or(Vector3::ZERO);
+        self.movement_from_planar_forward(forward, input, up)
+    }
+
+    /// Calculate the movement relative to a camera rotated by `yaw` radians around the up
+    /// direction.
+    ///
+    /// At zero yaw the camera looks at the negative Z axis (or, if the up direction is along the Z
+    /// axis, at the positive Y axis).
+    pub fn movement_from_camera_yaw(
+        &self,
+        yaw: Float,
+        input: Vector2,
+        up_direction: Dir3,
+    ) -> TnuaCameraRelativeMovement {
+        let up = up_direction.adjust_precision();
+        let reference_forward = Vector3::NEG_Z
+            .reject_from(up)
+            .try_normalize()
+            .unwrap_or_else(|| Vector3::Y.reject_from(up).normalize_or_zero());
+        let forward = Quaternion::from_axis_angle(up, yaw).mul_vec3(reference_forward);
+        self.movement_from_planar_forward(forward, input, up)
+    }
+
+    fn movement_from_planar_forward(
+        &self,
+        forward: Vector3,
+        input: Vector2,
+        up: Vector3,
+    ) -> TnuaCameraRelativeMovement {
+        let right = forward.cross(up);
+        let camera_forward = Dir3::new(forward.f32()).ok();
+        let magnitude = self.input_magnitude(input);
+        let Some(direction) = (right * input.x + forward * input.y)
+            .try_normalize()
+            .filter(|_| 0.0 < magnitude)
+        else {
+            return TnuaCameraRelativeMovement {
+                camera_forward,
+                ..Default::default()
+            };
+        };
+        TnuaCameraRelativeMovement {
+            direction: magnitude * direction,
+            desired_velocity: magnitude * self.max_speed * direction,
+            desired_forward: Dir3::new(direction.f32()).ok(),
+            camera_forward,
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{Float, Vector3};
+
+use crate::builtins::TnuaBuiltinJump;
+use crate::controller::TnuaController;
+use crate::subservient_sensors::TnuaSubservientSensor;
+use crate::{TnuaPipelineStages, TnuaProximitySensor, TnuaSystemSet, TnuaUserControlsSystemSet};
+
+pub struct TnuaCeilingSensorPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaCeilingSensorPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaCeilingSensorPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+/// A plugin required for making [`TnuaCeilingSensor`] work.
+impl Plugin for TnuaCeilingSensorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            update_ceiling_sensor
+                .in_set(TnuaSystemSet)
+                .after(TnuaUserControlsSystemSet)
+                .before(TnuaPipelineStages::Logic),
+        );
+    }
+}
+
+/// Stops [`TnuaBuiltinJump`] from rising when the character hits its head on a ceiling.
+///
+/// This will create a child entity with a proximity sensor pointed upward. While a jump is active
+/// and that sensor senses a ceiling, the jump will be moved to its fall section with
+/// [`TnuaBuiltinJumpState::bonk_ceiling`](crate::builtins::TnuaBuiltinJumpState::bonk_ceiling).
+///
+/// Using it requires two things:
+///
+/// 1. Adding the plugin [`TnuaCeilingSensorPlugin`].
+/// 2. Adding [`TnuaCeilingSensor`] as a component to the character entity.
+#[derive(Component)]
+pub struct TnuaCeilingSensor {
+    sensor_entity: Option<Entity>,
+    offset: Vector3,
+    range: Float,
+    modify_sensor: Box<dyn Send + Sync + Fn(&mut EntityCommands)>,
+}
+
+impl TnuaCeilingSensor {
+    /// Create a new ceiling sensor, to be added as a component to the character entity.
+    ///
+    /// # Arguments:
+    ///
+    /// * `offset` - the origin of the proximity sensor used to detect the ceiling. Should be placed
+    ///   at the top of the collider. The sensor is always pointed upwards.
+    /// * `range` - the distance from the `offset` at which a ceiling is considered to be touching
+    ///   the character's head.
+    /// * `modify_sensor` - a function called with the command that creates the sensor. This
+    ///   function has the opportunity to add things to the sensor entity - mostly cast-shape
+    ///   components.
+    pub fn new(
+        offset: Vector3,
+        range: Float,
+        modify_sensor: impl 'static + Send + Sync + Fn(&mut EntityCommands),
+    ) -> Self {
+        Self {
+            sensor_entity: None,
+            offset,
+            range,
+            modify_sensor: Box::new(modify_sensor),
+        }
+    }
+}
+
+fn update_ceiling_sensor(
+    mut query: Query<(Entity, &mut TnuaController, &mut TnuaCeilingSensor)>,
+    mut sensors_query: Query<(&mut TnuaProximitySensor, Has<TnuaSubservientSensor>)>,
+    mut commands: Commands,
+) {
+    for (owner_entity, mut controller, mut ceiling_sensor) in query.iter_mut() {
+        let Some((_, jump_state)) = controller.concrete_action_mut::<TnuaBuiltinJump>() else {
+            if let Some((mut subservient_sensor, true)) = ceiling_sensor
+                .sensor_entity
+                .and_then(|entity| sensors_query.get_mut(entity).ok())
+            {
+                // Turn it off
+                subservient_sensor.cast_range = 0.0;
+            }
+            continue;
+        };
+
+        let hit_ceiling = ceiling_sensor
+            .sensor_entity
+            .and_then(|entity| sensors_query.get(entity).ok())
+            .is_some_and(|(sensor, is_subservient)| is_subservient && sensor.output.is_some());
+        if hit_ceiling {
+            jump_state.bonk_ceiling();
+        }
+
+        let Ok((main_sensor, _)) = sensors_query.get(owner_entity) else {
+            continue;
+        };
+        let cast_direction = -main_sensor.cast_direction;
+
+        if let Some((mut subservient_sensor, true)) = ceiling_sensor
+            .sensor_entity
+            .and_then(|entity| sensors_query.get_mut(entity).ok())
+        {
+            subservient_sensor.cast_origin = ceiling_sensor.offset;
+            subservient_sensor.cast_direction = cast_direction;
+            subservient_sensor.cast_range = ceiling_sensor.range;
+        } else {
+            let mut cmd = commands.spawn((
+                Transform::default(),
+                TnuaSubservientSensor { owner_entity },
+                TnuaProximitySensor {
+                    cast_origin: ceiling_sensor.offset,
+                    cast_direction,
+                    cast_range: ceiling_sensor.range,
+                    ..Default::default()
+                },
+            ));
+            cmd.set_parent(owner_entity);
+            (ceiling_sensor.modify_sensor)(&mut cmd);
+            let sensor_entity = cmd.id();
+            ceiling_sensor.sensor_entity = Some(sensor_entity);
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::{StaticSystemParam, SystemParam};
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, Float, Vector3};
+use bevy_tnua_physics_integration_layer::obstacle_radar::TnuaObstacleRadar;
+use bevy_tnua_physics_integration_layer::spatial_ext::TnuaSpatialExt;
+
+use crate::radar_lens::{TnuaBlipSpatialRelation, TnuaRadarBlipLens, TnuaRadarLens};
+use crate::{TnuaPipelineStages, TnuaSystemSet, TnuaUserControlsSystemSet};
+
+/// A plugin required for making [`TnuaEnvironmentClassifier`] work.
+///
+/// The type parameter is the [`TnuaSpatialExt`] of the physics backend - e.g.
+/// `TnuaSpatialExtAvian3d` or `TnuaSpatialExtRapier2d`:
+///
+/// ```ignore
+/// app.add_plugins(TnuaEnvironmentClassifierPlugin::<TnuaSpatialExtAvian3d>::new(FixedUpdate));
+/// ```
+pub struct TnuaEnvironmentClassifierPlugin<X> {
+    schedule: InternedScheduleLabel,
+    _phantom: PhantomData<fn() -> X>,
+}
+
+impl<X> TnuaEnvironmentClassifierPlugin<X> {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<X> Default for TnuaEnvironmentClassifierPlugin<X> {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl<X> Plugin for TnuaEnvironmentClassifierPlugin<X>
+where
+    X: 'static + SystemParam,
+    for<'w, 's> X::Item<'w, 's>: TnuaSpatialExt,
+{
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            classify_environment_system::<X>
+                .in_set(TnuaSystemSet)
+                .after(TnuaPipelineStages::SubservientSensors)
+                .before(TnuaUserControlsSystemSet),
+        );
+    }
+}
+
+/// Marks a collider as something the character can climb on.
+///
+/// [`TnuaEnvironmentClassifier`] classifies blips with this component as
+/// [`TnuaEnvironmentKind::Climbable`].
+#[derive(Component, Default, Debug)]
+pub struct TnuaClimbable;
+
+/// The kind of an environment feature detected by [`TnuaEnvironmentClassifier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TnuaEnvironmentKind {
+    /// A flat obstacle next to the character that extends above
+    /// [`ledge_max_height`](TnuaEnvironmentClassifier::ledge_max_height).
+    Wall,
+    /// An obstacle next to the character, with its top between
+    /// [`low_obstacle_max_height`](TnuaEnvironmentClassifier::low_obstacle_max_height) and
+    /// [`ledge_max_height`](TnuaEnvironmentClassifier::ledge_max_height).
+    Ledge,
+    /// An obstacle above the character.
+    Ceiling,
+    /// An obstacle next to the character, with its top between
+    /// [`step_max_height`](TnuaEnvironmentClassifier::step_max_height) and
+    /// [`low_obstacle_max_height`](TnuaEnvironmentClassifier::low_obstacle_max_height).
+    LowObstacle,
+    /// A collider marked with [`TnuaClimbable`].
+    Climbable,
+    /// An obstacle next to the character, with its top between
+    /// [`min_obstacle_height`](TnuaEnvironmentClassifier::min_obstacle_height) and
+    /// [`step_max_height`](TnuaEnvironmentClassifier::step_max_height) - low enough to walk over.
+    Step,
+}
+
+/// An environment feature detected by [`TnuaEnvironmentClassifier`].
+#[derive(Debug, Clone)]
+pub struct TnuaClassifiedBlip {
+    /// The collider entity of the obstacle.
+    pub entity: Entity,
+    pub kind: TnuaEnvironmentKind,
+    /// The point on the obstacle that's closest to the character's position.
+    pub closest_point: Vector3,
+    /// The normal of the obstacle's surface at the closest point.
+    pub normal: Vector3,
+    /// The distance between the character's position and the closest point.
+    pub distance: Float,
+    /// The direction from the character to the obstacle. For obstacles that are next to the
+    /// character this is perpendicular to the up direction.
+    pub direction: Dir3,
+    /// The top of the obstacle, above the closest point. Only set for
+    /// [`Ledge`](TnuaEnvironmentKind::Ledge), [`LowObstacle`](TnuaEnvironmentKind::LowObstacle) and
+    /// [`Step`](TnuaEnvironmentKind::Step).
+    pub top: Option<Vector3>,
+    /// The height of [`top`](Self::top) above the character's feet.
+    pub top_height: Option<Float>,
+}
+
+/// Classifies the blips of the character's [`TnuaObstacleRadar`] into walls, ledges, ceilings,
+/// etc.
+///
+/// Using it requires three things:
+///
+/// 1. Adding the plugin [`TnuaEnvironmentClassifierPlugin`].
+/// 2. Adding a [`TnuaObstacleRadar`] to the character entity.
+/// 3. Adding [`TnuaEnvironmentClassifier`] as a component to the character entity (usually
+///    created with [`TnuaEnvironmentClassifier::new`]).
+///
+/// The classification is updated every frame before [`TnuaUserControlsSystemSet`], so the
+/// character controls system can use it to decide which actions to feed:
+///
+/// ```ignore
+/// if let Some(wall) = classifier.find(TnuaEnvironmentKind::Wall, left, 0.7) {
+///     controller.action(TnuaBuiltinWallSlide {
+///         wall_entity: Some(wall.entity),
+///         contact_point_with_wall: wall.closest_point,
+///         normal: Dir3::new(wall.normal.f32()).unwrap(),
+///         ..Default::default()
+///     });
+/// }
+/// ```
+///
+/// Heights are measured from the character's feet, which are assumed to be
+/// [`feet_offset`](Self::feet_offset) below the character's position.
+#[derive(Component, Clone, Debug)]
+pub struct TnuaEnvironmentClassifier {
+    /// The distance from the character's position to its feet.
+    ///
+    /// Should usually be the same as the
+    /// [`float_height`](crate::builtins::TnuaBuiltinWalk::float_height) of the walk basis.
+    pub feet_offset: Float,
+
+    /// Passed to [`TnuaRadarBlipLens::spatial_relation`] to determine if an obstacle is above,
+    /// below, or next to the character.
+    pub spatial_relation_threshold: Float,
+
+    /// Obstacles lower than this are considered part of the floor and are not classified.
+    pub min_obstacle_height: Float,
+
+    /// Obstacles no taller than this are classified as [`Step`](TnuaEnvironmentKind::Step).
+    pub step_max_height: Float,
+
+    /// Obstacles no taller than this are classified as
+    /// [`LowObstacle`](TnuaEnvironmentKind::LowObstacle).
+    pub low_obstacle_max_height: Float,
+
+    /// Obstacles no taller than this are classified as [`Ledge`](TnuaEnvironmentKind::Ledge).
+    /// Taller ones are classified as [`Wall`](TnuaEnvironmentKind::Wall) if they are flat enough.
+    pub ledge_max_height: Float,
+
+    /// The minimal [`flat_wall_score`](TnuaRadarBlipLens::flat_wall_score) for an obstacle to be
+    /// classified as a [`Wall`](TnuaEnvironmentKind::Wall).
+    pub wall_flatness_threshold: Float,
+
+    /// How far above and below the closest point to probe when calculating the
+    /// [`flat_wall_score`](TnuaRadarBlipLens::flat_wall_score).
+    pub wall_flatness_probe: Float,
+
+    classified: Vec<TnuaClassifiedBlip>,
+}
+
+impl Default for TnuaEnvironmentClassifier {
+    fn default() -> Self {
+        Self {
+            feet_offset: 1.0,
+            spatial_relation_threshold: 0.5,
+            min_obstacle_height: 0.05,
+            step_max_height: 0.5,
+            low_obstacle_max_height: 1.5,
+            ledge_max_height: 3.0,
+            wall_flatness_threshold: 0.8,
+            wall_flatness_probe: 1.0,
+            classified: Default::default(),
+        }
+    }
+}
+
+impl TnuaEnvironmentClassifier {
+    /// Create a classifier for a character whose feet are `feet_offset` below its position.
+    ///
+    /// The other parameters can be modified after creation.
+    pub fn new(feet_offset: Float) -> Self {
+        Self {
+            feet_offset,
+            ..Default::default()
+        }
+    }
+
+    /// Iterate over all the blips classified in the current frame.
+    pub fn iter(&self) -> impl '_ + Iterator<Item = &TnuaClassifiedBlip> {
+        self.classified.iter()
+    }
+
+    /// Iterate over all the blips of a specific kind.
+    pub fn iter_kind(
+        &self,
+        kind: TnuaEnvironmentKind,
+    ) -> impl '_ + Iterator<Item = &TnuaClassifiedBlip> {
+        self.classified.iter().filter(move |blip| blip.kind == kind)
+    }
+
+    /// Get the classification of a specific collider entity.
+    pub fn get(&self, entity: Entity) -> Option<&TnuaClassifiedBlip> {
+        self.classified.iter().find(|blip| blip.entity == entity)
+    }
+
+    /// Check if there is any blip of the specified kind.
+    pub fn has(&self, kind: TnuaEnvironmentKind) -> bool {
+        self.iter_kind(kind).next().is_some()
+    }
+
+    /// Find the closest blip of the specified kind in the specified direction.
+    ///
+    /// Only blips whose [`direction`](TnuaClassifiedBlip::direction) has a dot product of at least
+    /// `min_dot` with `direction` are considered.
+    pub fn find(
+        &self,
+        kind: TnuaEnvironmentKind,
+        direction: Dir3,
+        min_dot: Float,
+    ) -> Option<&TnuaClassifiedBlip> {
+        self.iter_kind(kind)
+            .filter(|blip| min_dot <= blip.direction.dot(*direction).adjust_precision())
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    }
+
+    fn classify<X: TnuaSpatialExt>(
+        &self,
+        blip: &TnuaRadarBlipLens<X>,
+        radar: &TnuaObstacleRadar,
+        is_climbable: bool,
+    ) -> Option<TnuaClassifiedBlip> {
+        let up = radar.up_direction();
+        let relation = blip.spatial_relation(self.spatial_relation_threshold);
+        let direction = match relation {
+            TnuaBlipSpatialRelation::Invalid => return None,
+            TnuaBlipSpatialRelation::Above => up,
+            TnuaBlipSpatialRelation::Aeside(direction) => direction,
+            // Obstacles lower than the character's center (like steps) are below it, so use the
+            // planar direction and let their height decide if they are part of the floor.
+            TnuaBlipSpatialRelation::Below => blip
+                .direction_to_closest_point()
+                .ok()
+                .and_then(|direction| Dir3::new(direction.reject_from_normalized(*up)).ok())
+                .unwrap_or(-up),
+        };
+        let blip_data = blip.blip_data();
+        let closest_point = blip_data.closest_point.get();
+        let mut classified = TnuaClassifiedBlip {
+            entity: blip.entity(),
+            kind: TnuaEnvironmentKind::Climbable,
+            closest_point,
+            normal: blip_data.normal,
+            distance: blip_data.distance,
+            direction,
+            top: None,
+            top_height: None,
+        };
+
+        if is_climbable {
+            return Some(classified);
+        }
+        if !blip.is_interactable() {
+            return None;
+        }
+        match relation {
+            TnuaBlipSpatialRelation::Above => {
+                classified.kind = TnuaEnvironmentKind::Ceiling;
+                return Some(classified);
+            }
+            TnuaBlipSpatialRelation::Invalid => return None,
+            TnuaBlipSpatialRelation::Aeside(_) | TnuaBlipSpatialRelation::Below => {}
+        }
+
+        let up_vec = up.adjust_precision();
+        let closest_point_height =
+            (closest_point - radar.tracked_position()).dot(up_vec) + self.feet_offset;
+        let probe_distance = self.ledge_max_height - closest_point_height;
+        let extent = if 0.0 < probe_distance {
+            blip.probe_extent_from_closest_point(up, probe_distance)
+        } else {
+            0.0
+        };
+        if 0.0 < probe_distance && extent < 0.9 * probe_distance {
+            let top_height = closest_point_height + extent;
+            if top_height < self.min_obstacle_height {
+                // Part of the floor
+                return None;
+            }
+            classified.top = Some(closest_point + extent * up_vec);
+            classified.top_height = Some(top_height);
+            classified.kind = if top_height <= self.step_max_height {
+                TnuaEnvironmentKind::Step
+            } else if top_height <= self.low_obstacle_max_height {
+                TnuaEnvironmentKind::LowObstacle
+            } else {
+                TnuaEnvironmentKind::Ledge
+            };
+            Some(classified)
+        } else if self.wall_flatness_threshold
+            <= blip.flat_wall_score(up, &[-self.wall_flatness_probe, self.wall_flatness_probe])
+        {
+            classified.kind = TnuaEnvironmentKind::Wall;
+            Some(classified)
+        } else {
+            None
+        }
+    }
+}
+
+fn classify_environment_system<X>(
+    mut query: Query<(&TnuaObstacleRadar, &mut TnuaEnvironmentClassifier)>,
+    spatial_ext: StaticSystemParam<X>,
+    climbable_query: Query<(), With<TnuaClimbable>>,
+) where
+    X: 'static + SystemParam,
+    for<'w, 's> X::Item<'w, 's>: TnuaSpatialExt,
+{
+    for (radar, mut classifier) in query.iter_mut() {
+        let radar_lens = TnuaRadarLens::new(radar, &*spatial_ext);
+        let classified = radar_lens
+            .iter_blips()
+            .filter_map(|blip| {
+                classifier.classify(&blip, radar, climbable_query.contains(blip.entity()))
+            })
+            .collect();
+        classifier.classified = classified;
+    }
+}
@@ -8,10 +8,22 @@
 //! although less flexible way.
 mod air_actions_tracking;
 mod blip_reuse_avoidance;
+mod camera_relative_input;
+mod ceiling_sensor;
 mod crouch_enforcer;
+mod environment_classifier;
+mod path_follower;
+mod sensor_shape_swap;
 mod simple_fall_through_platforms;
+mod speed_tiers;
 
 pub use air_actions_tracking::*;
 pub use blip_reuse_avoidance::*;
+pub use camera_relative_input::*;
+pub use ceiling_sensor::*;
 pub use crouch_enforcer::*;
+pub use environment_classifier::*;
+pub use path_follower::*;
+pub use sensor_shape_swap::*;
 pub use simple_fall_through_platforms::*;
+pub use speed_tiers::*;
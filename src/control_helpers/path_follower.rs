@@ -0,0 +1,369 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{AdjustPrecision, AsF32, Float, Vector3};
+
+use crate::builtins::{TnuaBuiltinCrouch, TnuaBuiltinJump, TnuaBuiltinWalk};
+use crate::controller::TnuaController;
+use crate::{TnuaRigidBodyTracker, TnuaUserControlsSystemSet};
+
+pub struct TnuaPathFollowerPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaPathFollowerPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaPathFollowerPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+/// A plugin required for making [`TnuaPathFollower`] work.
+impl Plugin for TnuaPathFollowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TnuaPathFollowerEvent>();
+        app.add_systems(
+            self.schedule,
+            follow_paths_system.in_set(TnuaUserControlsSystemSet),
+        );
+    }
+}
+
+/// How the character should get from the previous waypoint to a [`TnuaPathWaypoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TnuaPathLink {
+    /// Just walk there.
+    #[default]
+    Walk,
+    /// Jump (with [`TnuaPathFollower::jump`]) when starting to move toward the waypoint.
+    Jump,
+    /// Crouch (with [`TnuaPathFollower::crouch`]) while moving toward the waypoint.
+    Crouch,
+}
+
+/// A point on the path of a [`TnuaPathFollower`].
+#[derive(Debug, Clone, Copy)]
+pub struct TnuaPathWaypoint {
+    pub position: Vector3,
+    /// How to traverse the path from the previous waypoint (or from the character's position, for
+    /// the first waypoint) to this one.
+    pub link: TnuaPathLink,
+}
+
+impl TnuaPathWaypoint {
+    /// A waypoint reached by walking.
+    pub fn walk(position: Vector3) -> Self {
+        Self {
+            position,
+            link: TnuaPathLink::Walk,
+        }
+    }
+
+    /// A waypoint reached by jumping.
+    pub fn jump(position: Vector3) -> Self {
+        Self {
+            position,
+            link: TnuaPathLink::Jump,
+        }
+    }
+
+    /// A waypoint reached by crouching.
+    pub fn crouch(position: Vector3) -> Self {
+        Self {
+            position,
+            link: TnuaPathLink::Crouch,
+        }
+    }
+}
+
+/// Emitted by [`TnuaPathFollowerPlugin`] to report the progress of [`TnuaPathFollower`]s.
+#[derive(Event, Debug, Clone)]
+pub enum TnuaPathFollowerEvent {
+    /// The character reached a waypoint (other than the last one).
+    WaypointReached { entity: Entity, index: usize },
+    /// The character reached the last waypoint.
+    Finished { entity: Entity },
+    /// The character did not get closer to the current waypoint for
+    /// [`stuck_timeout`](TnuaPathFollower::stuck_timeout) seconds.
+    ///
+    /// The follower does not give up on the path - it's up to the game code to decide what to do
+    /// (e.g. recompute the path or [`clear`](TnuaPathFollower::clear) it)
+    Stuck { entity: Entity, index: usize },
+}
+
+/// Makes a character follow a list of waypoints - e.g. a path computed by a navmesh.
+///
+/// The path follower feeds the [`TnuaController`] by itself, so the character's control system
+/// should not feed it while a path is being followed (it can check
+/// [`is_following`](Self::is_following)).
+///
+/// Using it requires two things:
+///
+/// 1. Adding the plugin [`TnuaPathFollowerPlugin`].
+/// 2. Adding [`TnuaPathFollower`] as a component to the character entity (usually created with
+///    [`TnuaPathFollower::new`]).
+///
+/// Then, to make the character move, set a path:
+///
+/// ```no_run
+/// # use bevy_tnua::math::Vector3;
+/// # use bevy_tnua::control_helpers::{TnuaPathFollower, TnuaPathWaypoint};
+/// # let mut path_follower = TnuaPathFollower::default();
+/// path_follower.set_path([
+///     TnuaPathWaypoint::walk(Vector3::new(5.0, 0.0, 0.0)),
+///     TnuaPathWaypoint::jump(Vector3::new(8.0, 2.0, 0.0)),
+///     TnuaPathWaypoint::crouch(Vector3::new(12.0, 2.0, 0.0)),
+/// ]);
+/// ```
+///
+/// Distances are measured on the plane perpendicular to the character's up direction, so the
+/// waypoints' heights only matter for deciding where the character needs to go.
+#[derive(Component, Clone, Debug)]
+pub struct TnuaPathFollower {
+    /// The basis to feed. Its `desired_velocity` and `desired_forward` are set by the path
+    /// follower.
+    pub walk: TnuaBuiltinWalk,
+
+    /// The action to feed for [`TnuaPathLink::Jump`] links.
+    pub jump: TnuaBuiltinJump,
+
+    /// The action to feed for [`TnuaPathLink::Crouch`] links.
+    pub crouch: TnuaBuiltinCrouch,
+
+    /// The speed of the character when following the path.
+    pub speed: Float,
+
+    /// The speed of the character when following a [`TnuaPathLink::Crouch`] link.
+    pub crouch_speed: Float,
+
+    /// A waypoint is considered reached when the character is this close to it.
+    pub reach_distance: Float,
+
+    /// When the character is this close to a waypoint that is not the last one, it'll start
+    /// steering toward the next waypoint. This is only done between [`TnuaPathLink::Walk`] links.
+    pub corner_cutting_distance: Float,
+
+    /// When the character is this close to the last waypoint it'll start slowing down.
+    pub slowdown_distance: Float,
+
+    /// The character is considered stuck if it did not get closer to the current waypoint by at
+    /// least [`stuck_min_progress`](Self::stuck_min_progress) in this many seconds.
+    pub stuck_timeout: Float,
+
+    /// See [`stuck_timeout`](Self::stuck_timeout).
+    pub stuck_min_progress: Float,
+
+    waypoints: Vec<TnuaPathWaypoint>,
+    current_index: usize,
+    closest_distance: Float,
+    time_without_progress: Float,
+    is_stuck: bool,
+    fed_last_frame: bool,
+}
+
+impl Default for TnuaPathFollower {
+    fn default() -> Self {
+        Self {
+            walk: Default::default(),
+            jump: Default::default(),
+            crouch: Default::default(),
+            speed: 10.0,
+            crouch_speed: 2.0,
+            reach_distance: 0.5,
+            corner_cutting_distance: 2.0,
+            slowdown_distance: 2.0,
+            stuck_timeout: 1.0,
+            stuck_min_progress: 0.1,
+            waypoints: Default::default(),
+            current_index: 0,
+            closest_distance: Float::INFINITY,
+            time_without_progress: 0.0,
+            is_stuck: false,
+            fed_last_frame: false,
+        }
+    }
+}
+
+impl TnuaPathFollower {
+    /// Create a path follower that moves the character with the specified walk basis.
+    ///
+    /// The other parameters (like [`speed`](Self::speed) or [`jump`](Self::jump)) can be modified
+    /// after creation.
+    pub fn new(walk: TnuaBuiltinWalk) -> Self {
+        Self {
+            walk,
+            ..Default::default()
+        }
+    }
+
+    /// Start following a new path, replacing the current one.
+    pub fn set_path(&mut self, waypoints: impl IntoIterator<Item = TnuaPathWaypoint>) {
+        self.waypoints = waypoints.into_iter().collect();
+        self.current_index = 0;
+        self.reset_stuck_detection();
+    }
+
+    /// Stop following the path.
+    ///
+    /// The path follower will stop the character, and then stop feeding the controller.
+    pub fn clear(&mut self) {
+        self.set_path([]);
+    }
+
+    /// The path being followed - including the waypoints that were already reached.
+    pub fn waypoints(&self) -> &[TnuaPathWaypoint] {
+        &self.waypoints
+    }
+
+    /// The index of the waypoint the character is currently moving toward.
+    pub fn current_index(&self) -> usize {
+        self.current_index
+    }
+
+    /// The waypoint the character is currently moving toward.
+    pub fn current_waypoint(&self) -> Option<&TnuaPathWaypoint> {
+        self.waypoints.get(self.current_index)
+    }
+
+    /// Check if the character has a path it did not finish yet.
+    pub fn is_following(&self) -> bool {
+        self.current_index < self.waypoints.len()
+    }
+
+    /// The fraction of the path's waypoints that were reached, between 0.0 and 1.0.
+    pub fn progress(&self) -> Float {
+        if self.waypoints.is_empty() {
+            1.0
+        } else {
+            self.current_index as Float / self.waypoints.len() as Float
+        }
+    }
+
+    /// Check if the character is stuck - see [`stuck_timeout`](Self::stuck_timeout).
+    pub fn is_stuck(&self) -> bool {
+        self.is_stuck
+    }
+
+    fn reset_stuck_detection(&mut self) {
+        self.closest_distance = Float::INFINITY;
+        self.time_without_progress = 0.0;
+        self.is_stuck = false;
+    }
+
+    fn advance(&mut self, entity: Entity, events: &mut EventWriter<TnuaPathFollowerEvent>) {
+        self.current_index += 1;
+        self.reset_stuck_detection();
+        if self.is_following() {
+            events.send(TnuaPathFollowerEvent::WaypointReached {
+                entity,
+                index: self.current_index - 1,
+            });
+        } else {
+            events.send(TnuaPathFollowerEvent::Finished { entity });
+        }
+    }
+}
+
+fn follow_paths_system(
+    time: Res<Time>,
+    mut query: Query<(
+        Entity,
+        &TnuaRigidBodyTracker,
+        &mut TnuaController,
+        &mut TnuaPathFollower,
+    )>,
+    mut events: EventWriter<TnuaPathFollowerEvent>,
+) {
+    let frame_duration = time.delta().as_secs_f64() as Float;
+    for (entity, tracker, mut controller, mut follower) in query.iter_mut() {
+        let up = Dir3::new(-tracker.gravity.f32())
+            .unwrap_or(Dir3::Y)
+            .adjust_precision();
+        let planar_offset_to =
+            |point: Vector3| (point - tracker.translation).reject_from_normalized(up);
+
+        while let Some(waypoint) = follower.current_waypoint() {
+            if planar_offset_to(waypoint.position).length() <= follower.reach_distance {
+                follower.advance(entity, &mut events);
+            } else {
+                break;
+            }
+        }
+
+        let Some(waypoint) = follower.current_waypoint().copied() else {
+            if follower.fed_last_frame {
+                // The basis keeps its input when it's not fed, so it must be stopped explicitly.
+                follower.fed_last_frame = false;
+                controller.basis(TnuaBuiltinWalk {
+                    desired_velocity: Vector3::ZERO,
+                    desired_forward: None,
+                    ..follower.walk.clone()
+                });
+            }
+            continue;
+        };
+
+        let offset = planar_offset_to(waypoint.position);
+        let distance = offset.length();
+        let mut direction = offset.normalize_or_zero();
+
+        if distance + follower.stuck_min_progress <= follower.closest_distance {
+            follower.closest_distance = distance;
+            follower.time_without_progress = 0.0;
+        } else {
+            follower.time_without_progress += frame_duration;
+            if follower.stuck_timeout <= follower.time_without_progress && !follower.is_stuck {
+                follower.is_stuck = true;
+                events.send(TnuaPathFollowerEvent::Stuck {
+                    entity,
+                    index: follower.current_index,
+                });
+            }
+        }
+
+        let next_waypoint = follower.waypoints.get(follower.current_index + 1);
+        let speed = if let Some(next_waypoint) = next_waypoint {
+            if waypoint.link == TnuaPathLink::Walk
+                && next_waypoint.link == TnuaPathLink::Walk
+                && distance < follower.corner_cutting_distance
+            {
+                let next_direction = planar_offset_to(next_waypoint.position).normalize_or_zero();
+                let blend = 1.0 - distance / follower.corner_cutting_distance;
+                direction = direction
+                    .lerp(next_direction, blend)
+                    .normalize_or(direction);
+            }
+            follower.speed
+        } else {
+            follower.speed * (distance / follower.slowdown_distance).min(1.0)
+        };
+
+        let speed = match waypoint.link {
+            TnuaPathLink::Walk | TnuaPathLink::Jump => speed,
+            TnuaPathLink::Crouch => speed.min(follower.crouch_speed),
+        };
+
+        follower.fed_last_frame = true;
+        controller.basis(TnuaBuiltinWalk {
+            desired_velocity: speed * direction,
+            desired_forward: Dir3::new(direction.f32()).ok(),
+            ..follower.walk.clone()
+        });
+
+        match waypoint.link {
+            TnuaPathLink::Walk => {}
+            TnuaPathLink::Jump => {
+                controller.action(follower.jump.clone());
+            }
+            TnuaPathLink::Crouch => {
+                controller.action(follower.crouch.clone());
+            }
+        }
+    }
+}
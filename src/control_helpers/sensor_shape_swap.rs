@@ -0,0 +1,130 @@
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::controller::TnuaController;
+use crate::{TnuaPipelineStages, TnuaSystemSet};
+
+pub struct TnuaSensorShapeSwapPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaSensorShapeSwapPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaSensorShapeSwapPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+/// A plugin required for making [`TnuaSensorShapeSwap`] work.
+impl Plugin for TnuaSensorShapeSwapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            update_sensor_shape_swap
+                .in_set(TnuaSystemSet)
+                .after(TnuaPipelineStages::Logic),
+        );
+    }
+}
+
+/// Changes the character's proximity sensor shape while a specific action (like
+/// [`TnuaBuiltinDodgeRoll`](crate::builtins::TnuaBuiltinDodgeRoll)) is running.
+///
+/// Tnua itself is not aware of the physics backend's components, so the swapping is done with
+/// user-provided functions that typically insert the backend's `Tnua<physics-backend>SensorShape`
+/// component (e.g. - for Avian 3D, use `TnuaAvian3dSensorShape`) into the character entity.
+///
+/// Using it requires two things:
+///
+/// 1. Adding the plugin [`TnuaSensorShapeSwapPlugin`].
+/// 2. Adding [`TnuaSensorShapeSwap`] as a component to the character entity:
+///     ```no_run
+///     # use bevy::prelude::*;
+///     # use bevy_tnua::prelude::*;
+///     # use bevy_tnua::builtins::TnuaBuiltinDodgeRoll;
+///     # use bevy_tnua::control_helpers::TnuaSensorShapeSwap;
+///     # #[derive(Component)]
+///     # struct TnuaAvian3dSensorShape(f32);
+///     # fn spawn_character(mut commands: Commands, character_entity: Entity) {
+///     commands
+///         .entity(character_entity)
+///         .insert(TnuaSensorShapeSwap::new(
+///             TnuaBuiltinDodgeRoll::NAME,
+///             |cmd| {
+///                 // Use a smaller shape during the roll
+///                 cmd.insert(TnuaAvian3dSensorShape(0.25));
+///             },
+///             |cmd| {
+///                 cmd.insert(TnuaAvian3dSensorShape(0.49));
+///             },
+///         ));
+///     # }
+///     ```
+///
+/// Note that the shape only gets swapped after the controller starts or finishes the action, so
+/// the new shape will only be used by the sensor on the following frame.
+#[derive(Component)]
+pub struct TnuaSensorShapeSwap {
+    action_name: &'static str,
+    swap_in: Box<dyn Send + Sync + Fn(&mut EntityCommands)>,
+    swap_out: Box<dyn Send + Sync + Fn(&mut EntityCommands)>,
+    swapped: bool,
+}
+
+impl TnuaSensorShapeSwap {
+    /// Create a new sensor shape swap, to be added as a component to the character entity.
+    ///
+    /// # Arguments:
+    ///
+    /// * `action_name` - the [`NAME`](crate::TnuaAction::NAME) of the action to swap the shape
+    ///   for.
+    /// * `swap_in` - called with the character entity when the action starts. Use it to insert the
+    ///   sensor shape for the action.
+    /// * `swap_out` - called with the character entity when the action ends. Use it to restore the
+    ///   regular sensor shape (or to remove the sensor shape component, if the character does not
+    ///   normally use one)
+    pub fn new(
+        action_name: &'static str,
+        swap_in: impl 'static + Send + Sync + Fn(&mut EntityCommands),
+        swap_out: impl 'static + Send + Sync + Fn(&mut EntityCommands),
+    ) -> Self {
+        Self {
+            action_name,
+            swap_in: Box::new(swap_in),
+            swap_out: Box::new(swap_out),
+            swapped: false,
+        }
+    }
+
+    /// Whether the sensor shape is currently swapped for the action.
+    pub fn is_swapped(&self) -> bool {
+        self.swapped
+    }
+}
+
+fn update_sensor_shape_swap(
+    mut query: Query<(Entity, &TnuaController, &mut TnuaSensorShapeSwap)>,
+    mut commands: Commands,
+) {
+    for (entity, controller, mut swap) in query.iter_mut() {
+        let action_running = controller.action_name() == Some(swap.action_name);
+        if action_running == swap.swapped {
+            continue;
+        }
+        let mut cmd = commands.entity(entity);
+        if action_running {
+            (swap.swap_in)(&mut cmd);
+        } else {
+            (swap.swap_out)(&mut cmd);
+        }
+        swap.swapped = action_running;
+    }
+}
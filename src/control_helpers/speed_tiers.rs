@@ -0,0 +1,285 @@
+use bevy::prelude::*;
+
+use crate::builtins::TnuaBuiltinWalk;
+use crate::math::{Float, Vector3};
+
+/// A helper for moving the character at different speed tiers - e.g. walking, jogging and
+/// sprinting - with their own acceleration and turning rates.
+///
+/// Place this component on the character entity and call [`apply_to_walk`](Self::apply_to_walk)
+/// every frame from the player controls system. Tiers that drain stamina can be gated by adding a
+/// [`TnuaStamina`] component to the character and passing it to `apply_to_walk`.
+///
+/// The tier the character currently moves at is exposed via
+/// [`current_level`](Self::current_level) and [`current_tier`](Self::current_tier), for use in
+/// animation.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_tnua::prelude::*;
+/// # use bevy_tnua::control_helpers::{TnuaSpeedTier, TnuaSpeedTiers, TnuaStamina};
+/// # use bevy_tnua::math::*;
+/// # let mut controller = TnuaController::default();
+/// # let direction = Vector3::ZERO;
+/// # let sprint_pressed = false;
+/// # let frame_duration = 0.0;
+/// let mut speed_tiers = TnuaSpeedTiers::new([
+///     TnuaSpeedTier::new(3.0),
+///     TnuaSpeedTier::new(6.0),
+///     TnuaSpeedTier {
+///         stamina_drain: 20.0,
+///         turning_angvel: 5.0,
+///         ..TnuaSpeedTier::new(10.0)
+///     },
+/// ]);
+/// let mut stamina = TnuaStamina::new(100.0);
+///
+/// let mut walk = TnuaBuiltinWalk {
+///     float_height: 1.5,
+///     ..Default::default()
+/// };
+/// speed_tiers.apply_to_walk(
+///     &mut walk,
+///     direction,
+///     if sprint_pressed { 2.0 } else { 1.0 },
+///     Some(&mut stamina),
+///     frame_duration,
+/// );
+/// controller.basis(walk);
+/// ```
+#[derive(Component, Clone, Debug)]
+pub struct TnuaSpeedTiers {
+    /// The tiers, sorted from the slowest to the fastest.
+    pub tiers: Vec<TnuaSpeedTier>,
+
+    /// If `true`, fractional levels passed to [`apply_to_walk`](Self::apply_to_walk) will
+    /// interpolate between the adjacent tiers. Otherwise, they will be rounded down.
+    pub continuous: bool,
+
+    current_level: Float,
+}
+
+/// A single tier of [`TnuaSpeedTiers`].
+#[derive(Clone, Debug)]
+pub struct TnuaSpeedTier {
+    /// The speed of the character at this tier.
+    pub speed: Float,
+
+    /// The [`acceleration`](TnuaBuiltinWalk::acceleration) of the character at this tier.
+    pub acceleration: Float,
+
+    /// The [`turning_angvel`](TnuaBuiltinWalk::turning_angvel) of the character at this tier.
+    pub turning_angvel: Float,
+
+    /// The amount of stamina drained per second while moving at this tier.
+    ///
+    /// Tiers that drain stamina cannot be used while the [`TnuaStamina`] is exhausted.
+    pub stamina_drain: Float,
+}
+
+impl TnuaSpeedTier {
+    /// Create a tier with the given speed, and with the default acceleration and turning rate of
+    /// [`TnuaBuiltinWalk`].
+    pub fn new(speed: Float) -> Self {
+        let walk = TnuaBuiltinWalk::default();
+        Self {
+            speed,
+            acceleration: walk.acceleration,
+            turning_angvel: walk.turning_angvel,
+            stamina_drain: 0.0,
+        }
+    }
+
+    fn lerp(&self, other: &Self, t: Float) -> Self {
+        let lerp = |a: Float, b: Float| a + (b - a) * t;
+        Self {
+            speed: lerp(self.speed, other.speed),
+            acceleration: lerp(self.acceleration, other.acceleration),
+            turning_angvel: lerp(self.turning_angvel, other.turning_angvel),
+            stamina_drain: lerp(self.stamina_drain, other.stamina_drain),
+        }
+    }
+}
+
+impl TnuaSpeedTiers {
+    /// Create the helper with the given tiers, sorted from the slowest to the fastest.
+    pub fn new(tiers: impl IntoIterator<Item = TnuaSpeedTier>) -> Self {
+        Self {
+            tiers: tiers.into_iter().collect(),
+            continuous: false,
+            current_level: 0.0,
+        }
+    }
+
+    /// The level the character moved at when [`apply_to_walk`](Self::apply_to_walk) was last
+    /// called.
+    ///
+    /// This is the index of the tier, and when [`continuous`](Self::continuous) is `true` it can
+    /// be fractional.
+    pub fn current_level(&self) -> Float {
+        self.current_level
+    }
+
+    /// The index of the fastest tier that is not above the [current
+    /// level](Self::current_level).
+    pub fn current_tier(&self) -> usize {
+        self.current_level.max(0.0) as usize
+    }
+
+    /// The tier (interpolated if [`continuous`](Self::continuous)) at the given level, or `None`
+    /// if there are no tiers.
+    pub fn tier_at_level(&self, level: Float) -> Option<TnuaSpeedTier> {
+        let last_index = self.tiers.len().checked_sub(1)?;
+        let level = level.clamp(0.0, last_index as Float);
+        let index = level as usize;
+        let tier = &self.tiers[index];
+        if !self.continuous || index == last_index {
+            return Some(tier.clone());
+        }
+        Some(tier.lerp(&self.tiers[index + 1], level - index as Float))
+    }
+
+    /// The highest level the character can move at without draining stamina.
+    fn max_level_without_stamina(&self) -> Float {
+        let usable = self
+            .tiers
+            .iter()
+            .take_while(|tier| tier.stamina_drain <= 0.0)
+            .count();
+        usable.saturating_sub(1) as Float
+    }
+
+    /// Configure the walk basis to move in the given direction at the requested level.
+    ///
+    /// * `direction` - the direction of the movement. Its length should be between `0.0` and
+    ///   `1.0`, and the speed of the tier will be scaled by it.
+    /// * `requested_level` - the index of the tier to move at. If [`continuous`](Self::continuous)
+    ///   is `true` it can be fractional.
+    /// * `stamina` - if given, tiers that drain stamina will drain it while moving, and will not be
+    ///   used while it is exhausted. The stamina will also be regenerated when not drained, so
+    ///   this method should be called every frame.
+    /// * `frame_duration` - the duration of the current frame, in seconds.
+    pub fn apply_to_walk(
+        &mut self,
+        walk: &mut TnuaBuiltinWalk,
+        direction: Vector3,
+        requested_level: Float,
+        stamina: Option<&mut TnuaStamina>,
+        frame_duration: Float,
+    ) {
+        let mut level = if self.continuous {
+            requested_level
+        } else {
+            requested_level.floor()
+        };
+        if stamina
+            .as_ref()
+            .is_some_and(|stamina| stamina.is_exhausted())
+        {
+            level = level.min(self.max_level_without_stamina());
+        }
+        let Some(tier) = self.tier_at_level(level) else {
+            walk.desired_velocity = Vector3::ZERO;
+            return;
+        };
+        self.current_level = level.clamp(0.0, (self.tiers.len() - 1) as Float);
+
+        walk.desired_velocity = tier.speed * direction.clamp_length_max(1.0);
+        walk.acceleration = tier.acceleration;
+        walk.turning_angvel = tier.turning_angvel;
+
+        if let Some(stamina) = stamina {
+            let drain = if direction == Vector3::ZERO {
+                0.0
+            } else {
+                tier.stamina_drain * frame_duration
+            };
+            stamina.update(drain, frame_duration);
+        }
+    }
+}
+
+/// Stamina for gating [`TnuaSpeedTiers`] that drain it.
+///
+/// When the stamina runs out it becomes exhausted, and tiers that drain stamina cannot be used
+/// until it regenerates up to the [`recovery_threshold`](Self::recovery_threshold).
+#[derive(Component, Clone, Debug)]
+pub struct TnuaStamina {
+    /// The maximum amount of stamina.
+    pub max: Float,
+
+    /// The amount of stamina regenerated per second while it is not drained.
+    pub regeneration_rate: Float,
+
+    /// The time, in seconds, after the stamina was last drained before it starts regenerating.
+    pub regeneration_delay: Float,
+
+    /// The amount of stamina required for the stamina to no longer be exhausted after it ran out.
+    pub recovery_threshold: Float,
+
+    current: Float,
+    exhausted: bool,
+    time_since_drained: Float,
+}
+
+impl TnuaStamina {
+    /// Create a full stamina with the given maximum.
+    pub fn new(max: Float) -> Self {
+        Self {
+            max,
+            regeneration_rate: 0.25 * max,
+            regeneration_delay: 1.0,
+            recovery_threshold: 0.25 * max,
+            current: max,
+            exhausted: false,
+            time_since_drained: Float::INFINITY,
+        }
+    }
+
+    /// The current amount of stamina.
+    pub fn current(&self) -> Float {
+        self.current
+    }
+
+    /// The current amount of stamina, as a fraction of the [maximum](Self::max).
+    pub fn fraction(&self) -> Float {
+        if 0.0 < self.max {
+            self.current / self.max
+        } else {
+            0.0
+        }
+    }
+
+    /// Whether the stamina ran out and did not recover yet.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Set the current amount of stamina (e.g. when the character drinks a potion).
+    pub fn set_current(&mut self, current: Float) {
+        self.current = current.clamp(0.0, self.max);
+        if self.exhausted && self.recovery_threshold <= self.current {
+            self.exhausted = false;
+        }
+    }
+
+    /// Drain the given amount of stamina, or regenerate it if the amount is zero.
+    ///
+    /// This is called automatically by [`TnuaSpeedTiers::apply_to_walk`], but can also be used
+    /// for draining stamina by other actions.
+    pub fn update(&mut self, drain: Float, frame_duration: Float) {
+        if 0.0 < drain {
+            self.time_since_drained = 0.0;
+            self.current -= drain;
+            if self.current <= 0.0 {
+                self.current = 0.0;
+                self.exhausted = true;
+            }
+            return;
+        }
+        self.time_since_drained += frame_duration;
+        if self.regeneration_delay <= self.time_since_drained {
+            self.set_current(self.current + frame_duration * self.regeneration_rate);
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Per-character gravity that comes from gravity field entities in the world.
+//!
+//! Tnua derives the up direction of a character from the gravity applied to it, so changing the
+//! gravity direction is all it takes to make [`TnuaBuiltinWalk`](crate::builtins::TnuaBuiltinWalk),
+//! the jump calculations and the ground sensor rotate with it. This module computes that gravity
+//! each frame from [`TnuaGravityField`] entities and writes it into the
+//! [`TnuaGravity`](crate::TnuaGravity) of every character marked with
+//! [`TnuaGravityFieldsSubject`].
+//!
+//! Using it requires three things:
+//!
+//! 1. Adding the plugin [`TnuaGravityFieldsPlugin`] (in the same schedule as the rest of Tnua).
+//! 2. Spawning entities with a [`TnuaGravityField`] and a `Transform`.
+//! 3. Adding [`TnuaGravityFieldsSubject`] to the character entities. This will also add a
+//!    [`TnuaGravity`](crate::TnuaGravity) component to them, which the physics backend will use
+//!    instead of the global gravity.
+//!
+//! Note that for the character to be able to walk around a planet, its rotation must not be
+//! locked - otherwise it will not be able to align itself with the changing up direction.
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+use bevy::prelude::*;
+use bevy_tnua_physics_integration_layer::math::{
+    AdjustPrecision, AsF32, Float, Quaternion, Vector3,
+};
+
+use crate::{TnuaGravity, TnuaPipelineStages, TnuaSystemSet, TnuaToggle};
+
+/// A plugin required for making [`TnuaGravityField`] and [`TnuaGravityFieldsSubject`] work.
+pub struct TnuaGravityFieldsPlugin {
+    schedule: InternedScheduleLabel,
+}
+
+impl TnuaGravityFieldsPlugin {
+    pub fn new(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
+
+impl Default for TnuaGravityFieldsPlugin {
+    fn default() -> Self {
+        Self::new(Update)
+    }
+}
+
+impl Plugin for TnuaGravityFieldsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            self.schedule,
+            apply_gravity_fields_system
+                .in_set(TnuaSystemSet)
+                .before(TnuaPipelineStages::Sensors),
+        );
+    }
+}
+
+/// An entity that applies gravity to [subject](TnuaGravityFieldsSubject) characters inside it.
+///
+/// The field is positioned and rotated by the entity's `GlobalTransform` (scale is ignored).
+///
+/// When a character is inside multiple fields, only the fields with the highest
+/// [`priority`](Self::priority) are considered and their gravity is averaged, weighted by how deep
+/// inside each field the character is (see [`blend_distance`](Self::blend_distance)). If these
+/// weights add up to less than `1.0`, the remainder is taken from the fields with the next
+/// priority, and eventually from the subject's
+/// [`fallback_gravity`](TnuaGravityFieldsSubject::fallback_gravity).
+#[derive(Component, Clone, Debug)]
+pub struct TnuaGravityField {
+    /// The shape of the field, which determines both its area of effect and the direction of the
+    /// gravity inside it.
+    pub shape: TnuaGravityFieldShape,
+
+    /// The magnitude of the gravity inside the field.
+    pub strength: Float,
+
+    /// Fields with higher priority override fields with lower priority.
+    pub priority: i32,
+
+    /// The depth, from the edge of the field, at which the field reaches its full weight.
+    ///
+    /// A character standing exactly on the edge will not be affected by the field at all, and the
+    /// influence grows linearly until it reaches this distance inside the field. Set to `0.0` to
+    /// make the field fully effective at its edge.
+    pub blend_distance: Float,
+}
+
+impl Default for TnuaGravityField {
+    fn default() -> Self {
+        Self {
+            shape: TnuaGravityFieldShape::Point {
+                radius: Float::INFINITY,
+            },
+            strength: 9.81,
+            priority: 0,
+            blend_distance: 0.0,
+        }
+    }
+}
+
+/// The shape of a [`TnuaGravityField`].
+#[derive(Clone, Debug)]
+pub enum TnuaGravityFieldShape {
+    /// Pull toward the field's position. Useful for round planets.
+    Point {
+        /// The field affects characters within this distance from its position.
+        radius: Float,
+    },
+    /// Pull toward a line segment going through the field's position. Useful for cylindrical
+    /// planets and tubes.
+    Cylinder {
+        /// The direction of the line, in the field's local coordinates.
+        axis: Dir3,
+        /// The field affects characters within this distance from the line.
+        radius: Float,
+        /// Half the length of the line segment.
+        half_length: Float,
+    },
+    /// Pull in a constant direction inside a box.
+    Volume {
+        /// The direction of the gravity, in the field's local coordinates.
+        direction: Dir3,
+        /// Half the size of the box, in the field's local coordinates.
+        half_extents: Vector3,
+    },
+}
+
+impl TnuaGravityField {
+    /// Calculate the direction of the gravity this field applies at `point`, together with the
+    /// weight of the field at that point.
+    ///
+    /// Returns `None` if the point is outside the field.
+    pub fn evaluate(&self, transform: &GlobalTransform, point: Vector3) -> Option<(Dir3, Float)> {
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+        let rotation = rotation.adjust_precision();
+        let offset = point - translation.adjust_precision();
+        let (direction, depth) = match &self.shape {
+            TnuaGravityFieldShape::Point { radius } => {
+                let (direction, distance) = Dir3::new_and_length(-offset.f32()).ok()?;
+                (direction, radius - distance.adjust_precision())
+            }
+            TnuaGravityFieldShape::Cylinder {
+                axis,
+                radius,
+                half_length,
+            } => {
+                let axis = rotation.mul_vec3(axis.adjust_precision());
+                let along_axis = offset.dot(axis);
+                let (direction, distance) =
+                    Dir3::new_and_length(-offset.reject_from_normalized(axis).f32()).ok()?;
+                let depth =
+                    (radius - distance.adjust_precision()).min(half_length - along_axis.abs());
+                (direction, depth)
+            }
+            TnuaGravityFieldShape::Volume {
+                direction,
+                half_extents,
+            } => {
+                let local_offset = rotation.inverse().mul_vec3(offset);
+                let depth = (*half_extents - local_offset.abs()).min_element();
+                let direction =
+                    Dir3::new(rotation.mul_vec3(direction.adjust_precision()).f32()).ok()?;
+                (direction, depth)
+            }
+        };
+        if depth < 0.0 {
+            return None;
+        }
+        let weight = if 0.0 < self.blend_distance {
+            (depth / self.blend_distance).min(1.0)
+        } else {
+            1.0
+        };
+        Some((direction, weight))
+    }
+}
+
+/// Add this to a character entity to have its gravity set by [`TnuaGravityField`]s.
+///
+/// Requires [`TnuaGravityFieldsPlugin`]. The gravity is not updated while the character's
+/// [`TnuaToggle`] is not [`Enabled`](TnuaToggle::Enabled).
+#[derive(Component, Clone, Debug)]
+#[require(TnuaGravity(initial_gravity))]
+pub struct TnuaGravityFieldsSubject {
+    /// The gravity to use when the character is not inside any field.
+    pub fallback_gravity: Vector3,
+
+    /// The maximum speed, in radians per second, at which the gravity direction may turn.
+    ///
+    /// Walking around a planet changes the gravity direction gradually anyway, but moving from
+    /// one field to another can change it abruptly. Limiting the turning speed gives the character
+    /// time to straighten up with the new up direction. Set to [`Float::INFINITY`] to disable the
+    /// limit.
+    pub max_turning_speed: Float,
+
+    dominant_field: Option<Entity>,
+}
+
+fn initial_gravity() -> TnuaGravity {
+    TnuaGravity(Vector3::ZERO)
+}
+
+impl Default for TnuaGravityFieldsSubject {
+    fn default() -> Self {
+        Self {
+            fallback_gravity: -9.81 * Vector3::Y,
+            max_turning_speed: 3.0,
+            dominant_field: None,
+        }
+    }
+}
+
+impl TnuaGravityFieldsSubject {
+    /// The field with the highest weight among the fields that affected the character in the last
+    /// frame, or `None` if the character was only affected by the fallback gravity.
+    pub fn dominant_field(&self) -> Option<Entity> {
+        self.dominant_field
+    }
+}
+
+fn apply_gravity_fields_system(
+    time: Res<Time>,
+    fields_query: Query<(Entity, &TnuaGravityField, &GlobalTransform)>,
+    mut subjects_query: Query<(
+        &GlobalTransform,
+        &mut TnuaGravityFieldsSubject,
+        &mut TnuaGravity,
+        Option<&TnuaToggle>,
+    )>,
+) {
+    let frame_duration = time.delta().as_secs_f64() as Float;
+    let mut contributions = Vec::new();
+    for (subject_transform, mut subject, mut gravity, tnua_toggle) in subjects_query.iter_mut() {
+        match tnua_toggle.copied().unwrap_or_default() {
+            TnuaToggle::Disabled | TnuaToggle::SenseOnly => continue,
+            TnuaToggle::Enabled => {}
+        }
+        let position = subject_transform.translation().adjust_precision();
+
+        contributions.clear();
+        contributions.extend(
+            fields_query
+                .iter()
+                .filter_map(|(entity, field, transform)| {
+                    let (direction, weight) = field.evaluate(transform, position)?;
+                    (0.0 < weight).then_some((
+                        entity,
+                        field.priority,
+                        weight,
+                        direction.adjust_precision() * field.strength,
+                    ))
+                }),
+        );
+        contributions.sort_by_key(|(_, priority, _, _)| std::cmp::Reverse(*priority));
+
+        let mut target_gravity = Vector3::ZERO;
+        let mut remaining_weight: Float = 1.0;
+        let mut dominant_field: Option<(Entity, Float)> = None;
+        for group in contributions.chunk_by(|a, b| a.1 == b.1) {
+            let total_weight: Float = group.iter().map(|(_, _, weight, _)| *weight).sum();
+            let weighted_gravity: Vector3 = group
+                .iter()
+                .map(|(_, _, weight, gravity)| *weight * *gravity)
+                .sum();
+            let portion = total_weight.min(1.0) * remaining_weight;
+            target_gravity += portion * weighted_gravity / total_weight;
+            for (entity, _, weight, _) in group.iter() {
+                let effective_weight = portion * weight / total_weight;
+                if dominant_field.is_none_or(|(_, best_weight)| best_weight < effective_weight) {
+                    dominant_field = Some((*entity, effective_weight));
+                }
+            }
+            remaining_weight -= portion;
+            if remaining_weight <= 0.0 {
+                break;
+            }
+        }
+        target_gravity += remaining_weight.max(0.0) * subject.fallback_gravity;
+        subject.dominant_field = dominant_field.map(|(entity, _)| entity);
+
+        gravity.0 = rotate_gravity_towards(
+            gravity.0,
+            target_gravity,
+            subject.max_turning_speed * frame_duration,
+        );
+    }
+}
+
+fn rotate_gravity_towards(current: Vector3, target: Vector3, max_angle: Float) -> Vector3 {
+    let (Some(current_direction), Some(target_direction)) =
+        (current.try_normalize(), target.try_normalize())
+    else {
+        return target;
+    };
+    let angle = current_direction.angle_between(target_direction);
+    if angle <= max_angle {
+        return target;
+    }
+    let rotation = Quaternion::from_rotation_arc(current_direction, target_direction);
+    let partial_rotation = Quaternion::IDENTITY.slerp(rotation, max_angle / angle);
+    partial_rotation.mul_vec3(current_direction) * target.length()
+}
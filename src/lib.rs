@@ -131,6 +131,7 @@ mod basis_action_traits;
 pub mod builtins;
 pub mod control_helpers;
 pub mod controller;
+pub mod gravity_fields;
 pub mod radar_lens;
 pub mod util;
 pub use animating_helper::{TnuaAnimatingState, TnuaAnimatingStateDirective};
@@ -3,10 +3,12 @@ use std::cell::OnceCell;
 use crate::math::{AdjustPrecision, AsF32, Float, Vector3};
 use bevy::{math::InvalidDirectionError, prelude::*};
 use bevy_tnua_physics_integration_layer::{
-    obstacle_radar::TnuaObstacleRadar,
-    spatial_ext::{TnuaPointProjectionResult, TnuaSpatialExt},
+    obstacle_radar::{TnuaObstacleRadar, TnuaRadarBlipData},
+    spatial_ext::{TnuaDynamicBodyData, TnuaPointProjectionResult, TnuaSpatialExt},
 };
 
+use crate::builtins::TnuaBuiltinWalkDynamicContact;
+
 pub struct TnuaRadarLens<'a, X: TnuaSpatialExt> {
     radar: &'a TnuaObstacleRadar,
     ext: &'a X,
@@ -23,8 +25,43 @@ impl<'a, X: TnuaSpatialExt> TnuaRadarLens<'a, X> {
                 radar_lens: self,
                 entity,
                 collider_data: self.ext.fetch_collider_data(entity)?,
-                closest_point_cache: OnceCell::new(),
-                closest_point_normal_cache: OnceCell::new(),
+                blip_data_cache: OnceCell::new(),
+            })
+        })
+    }
+
+    /// Collect the dynamic rigid bodies the character is touching, for feeding into
+    /// [`TnuaBuiltinWalk::dynamic_contacts`](crate::builtins::TnuaBuiltinWalk::dynamic_contacts).
+    ///
+    /// A body is considered touching if its collider is within `contact_distance` of the
+    /// character's position - so this should be a bit more than the distance from the center of
+    /// the character's collider to its sides.
+    pub fn dynamic_contacts(
+        &self,
+        contact_distance: Float,
+    ) -> impl '_ + Iterator<Item = TnuaBuiltinWalkDynamicContact> {
+        let character_mass = self
+            .ext
+            .dynamic_body_data(self.radar.tracked_entity())
+            .map(|data| data.mass)
+            .filter(|mass| 0.0 < *mass)
+            .unwrap_or(1.0);
+        self.iter_blips().filter_map(move |blip| {
+            if !blip.is_interactable() {
+                return None;
+            }
+            let body_data = blip.dynamic_body_data()?;
+            if body_data.body_entity == self.radar.tracked_entity() {
+                return None;
+            }
+            if contact_distance < blip.distance() {
+                return None;
+            }
+            Some(TnuaBuiltinWalkDynamicContact {
+                entity: body_data.body_entity,
+                direction: blip.direction_to_closest_point().ok()?,
+                relative_mass: body_data.mass / character_mass,
+                linvel: body_data.linvel,
             })
         })
     }
@@ -34,8 +71,7 @@ pub struct TnuaRadarBlipLens<'a, X: TnuaSpatialExt> {
     radar_lens: &'a TnuaRadarLens<'a, X>,
     entity: Entity,
     pub collider_data: X::ColliderData<'a>,
-    closest_point_cache: OnceCell<TnuaPointProjectionResult>,
-    closest_point_normal_cache: OnceCell<Vector3>,
+    blip_data_cache: OnceCell<TnuaRadarBlipData>,
 }
 
 impl<X: TnuaSpatialExt> TnuaRadarBlipLens<'_, X> {
@@ -47,22 +83,44 @@ impl<X: TnuaSpatialExt> TnuaRadarBlipLens<'_, X> {
         self.entity
     }
 
+    /// The mass and velocity of the blip's rigid body, if it is dynamic.
+    pub fn dynamic_body_data(&self) -> Option<TnuaDynamicBodyData> {
+        self.radar_lens.ext.dynamic_body_data(self.entity)
+    }
+
     pub fn is_interactable(&self) -> bool {
         self.radar_lens
             .ext
             .can_interact(self.radar().tracked_entity(), self.entity)
     }
 
-    pub fn closest_point(&self) -> TnuaPointProjectionResult {
-        *self.closest_point_cache.get_or_init(|| {
-            self.radar_lens.ext.project_point(
+    /// The contact data of the blip.
+    ///
+    /// This is computed by the radar on first access and shared with all the other lenses for the
+    /// rest of the frame. If the radar could not compute it, it'll be computed here (and cached for
+    /// the lifetime of the blip lens)
+    pub fn blip_data(&self) -> &TnuaRadarBlipData {
+        if let Some(blip_data) = self.radar().blip_data(self.entity, self.radar_lens.ext) {
+            return blip_data;
+        }
+        self.blip_data_cache.get_or_init(|| {
+            TnuaRadarBlipData::compute(
+                self.radar_lens.ext,
                 self.radar().tracked_position(),
-                false,
                 &self.collider_data,
             )
         })
     }
 
+    pub fn closest_point(&self) -> TnuaPointProjectionResult {
+        self.blip_data().closest_point
+    }
+
+    /// The distance from the character's position to the [`closest_point`](Self::closest_point).
+    pub fn distance(&self) -> Float {
+        self.blip_data().distance
+    }
+
     pub fn closest_point_from(&self, point: Vector3, solid: bool) -> TnuaPointProjectionResult {
         self.radar_lens
             .ext
@@ -125,6 +183,40 @@ impl<X: TnuaSpatialExt> TnuaRadarBlipLens<'_, X> {
         (closest_above - closest_point).dot(direction.adjust_precision())
     }
 
+    /// Find the walkable top surface of the blip's collider, above the
+    /// [`closest_point`](Self::closest_point).
+    ///
+    /// A ray is cast down from `max_height` above the closest point, moved `depth` into the
+    /// collider (against [`normal_from_closest_point`](Self::normal_from_closest_point)). The point
+    /// it hits is returned if the surface there is no steeper than `max_slope` (in radians).
+    ///
+    /// If the collider is higher than `max_height` above the closest point, `None` is returned.
+    /// This is useful for climbing over the top of a climbable object - see
+    /// [`TnuaBuiltinClimb::top_surface`](crate::builtins::TnuaBuiltinClimb::top_surface).
+    pub fn top_surface(
+        &self,
+        up: Dir3,
+        max_height: Float,
+        depth: Float,
+        max_slope: Float,
+    ) -> Option<Vector3> {
+        let closest_point = self.closest_point().outside()?;
+        let up = up.adjust_precision();
+        let inward = -self
+            .normal_from_closest_point()
+            .reject_from(up)
+            .try_normalize()?;
+        let origin = closest_point + depth * inward + max_height * up;
+        let (time_of_impact, normal) =
+            self.radar_lens
+                .ext
+                .cast_ray(origin, -up, max_height, &self.collider_data)?;
+        if time_of_impact <= 0.0 || max_slope < normal.angle_between(up) {
+            return None;
+        }
+        Some(origin - time_of_impact * up)
+    }
+
     pub fn direction_to_closest_point(&self) -> Result<Dir3, InvalidDirectionError> {
         match self.closest_point() {
             TnuaPointProjectionResult::Outside(closest_point) => {
@@ -137,30 +229,7 @@ impl<X: TnuaSpatialExt> TnuaRadarBlipLens<'_, X> {
     }
 
     pub fn normal_from_closest_point(&self) -> Vector3 {
-        *self.closest_point_normal_cache.get_or_init(|| {
-            let origin = self.radar().tracked_position();
-
-            let get_normal = |closest_point: Vector3| -> Vector3 {
-                let Some(direction) = (closest_point - origin).try_normalize() else {
-                    return Vector3::ZERO;
-                };
-                let Some((_, normal)) = self.radar_lens.ext.cast_ray(
-                    origin,
-                    direction,
-                    Float::INFINITY,
-                    &self.collider_data,
-                ) else {
-                    warn!("Unable to query normal to already-found closest point");
-                    return Vector3::ZERO;
-                };
-                normal
-            };
-
-            match self.closest_point() {
-                TnuaPointProjectionResult::Outside(closest_point) => get_normal(closest_point),
-                TnuaPointProjectionResult::Inside(closest_point) => -get_normal(closest_point),
-            }
-        })
+        self.blip_data().normal
     }
 
     pub fn spatial_relation(&self, threshold: Float) -> TnuaBlipSpatialRelation {
@@ -181,6 +181,24 @@ pub fn rotation_arc_around_axis(
     Some(rotation_to_set_forward.xyz().z)
 }
 
+/// Linearly interpolate a value from a list of `(key, value)` entries sorted by their keys.
+///
+/// Keys outside the range of the entries are clamped to it. Returns `None` if there are no
+/// entries.
+pub(crate) fn interpolate_sorted_entries(entries: &[(Float, Float)], key: Float) -> Option<Float> {
+    let after = entries.iter().position(|(entry_key, _)| key < *entry_key);
+    Some(match after {
+        None => entries.last()?.1,
+        Some(0) => entries[0].1,
+        Some(index) => {
+            let (key_before, value_before) = entries[index - 1];
+            let (key_after, value_after) = entries[index];
+            let ratio = (key - key_before) / (key_after - key_before);
+            value_before + ratio * (value_after - value_before)
+        }
+    })
+}
+
 /// Temporary until we get an official release of the physics integration layer crate with
 /// `calc_boost` in it.
 pub(crate) fn calc_boost(